@@ -1,6 +1,19 @@
+use crate::bytes::xor_byte_array;
+use crate::gf256::{Gf256, AES_POLYNOMIAL};
+
 // for AES-128 per FIPS 197:
 // 16 byte (128 bit key) (4 word)
 // 10 rounds
+pub fn encrypt_aes_128_ecb(key: &[u8], plaintext: &[u8]) -> Vec<u8> {
+    const N_K: usize = 4;
+    const N_R: usize = 10;
+    let w = key_expansion(key, N_K, N_R);
+    plaintext
+        .chunks(16)
+        .flat_map(|block| cypher(block.try_into().unwrap(), N_R, w.as_slice()))
+        .collect()
+}
+
 pub fn decrypt_aes_128_ecb(key: &[u8], cyphertext: &[u8]) -> Vec<u8> {
     const N_K: usize = 4;
     const N_R: usize = 10;
@@ -11,17 +24,84 @@ pub fn decrypt_aes_128_ecb(key: &[u8], cyphertext: &[u8]) -> Vec<u8> {
         .collect()
 }
 
-type State = [u32; 4];
+pub fn encrypt_aes_128_cbc(key: &[u8], iv: &[u8], plaintext: &[u8]) -> Vec<u8> {
+    const N_K: usize = 4;
+    const N_R: usize = 10;
+    assert!(
+        plaintext.len().is_multiple_of(16),
+        "pad the plaintext first"
+    );
+    let w = key_expansion(key, N_K, N_R);
+    let mut previous: [u8; 16] = iv.try_into().unwrap();
+    let mut cyphertext = Vec::with_capacity(plaintext.len());
+    for block in plaintext.chunks(16) {
+        let mixed = xor_byte_array(block, &previous);
+        previous = cypher(mixed.try_into().unwrap(), N_R, w.as_slice());
+        cyphertext.extend_from_slice(&previous);
+    }
+    cyphertext
+}
+
+pub fn decrypt_aes_128_cbc(key: &[u8], iv: &[u8], cyphertext: &[u8]) -> Vec<u8> {
+    const N_K: usize = 4;
+    const N_R: usize = 10;
+    let w = key_expansion(key, N_K, N_R);
+    let mut previous = iv;
+    let mut plaintext = Vec::with_capacity(cyphertext.len());
+    for block in cyphertext.chunks(16) {
+        let decrypted = inv_cypher(block.try_into().unwrap(), N_R, w.as_slice());
+        plaintext.append(&mut xor_byte_array(&decrypted, previous));
+        previous = block;
+    }
+    plaintext
+}
 
-pub fn inv_cypher(block: [u8; 16], n_r: usize, w: &[u32]) -> [u8; 16] {
+pub(crate) type State = [u32; 4];
+
+pub(crate) fn state_from_block(block: [u8; 16]) -> State {
     let mut state: State = [0u32; 4];
     for (i, chunk) in block.chunks_exact(4).enumerate() {
-        state[i] = u32::from_ne_bytes(chunk.try_into().unwrap());
+        state[i] = u32::from_le_bytes(chunk.try_into().unwrap());
     }
+    state
+}
 
+pub(crate) fn block_from_state(state: State) -> [u8; 16] {
+    state
+        .map(|x| x.to_le_bytes())
+        .iter()
+        .flatten()
+        .copied()
+        .collect::<Vec<u8>>()
+        .try_into()
+        .unwrap()
+}
+
+pub fn cypher(block: [u8; 16], n_r: usize, w: &[u32]) -> [u8; 16] {
+    let mut state = state_from_block(block);
+
+    add_round_key(&mut state, w[0..=3].try_into().unwrap());
+
+    for round in 1..n_r {
+        sub_bytes(&mut state);
+        shift_rows(&mut state);
+        mix_columns(&mut state);
+        add_round_key(&mut state, w[4 * round..=4 * round + 3].try_into().unwrap());
+    }
+
+    sub_bytes(&mut state);
+    shift_rows(&mut state);
     add_round_key(&mut state, w[4 * n_r..=4 * n_r + 3].try_into().unwrap());
 
-    for round in n_r - 1..=1 {
+    block_from_state(state)
+}
+
+pub fn inv_cypher(block: [u8; 16], n_r: usize, w: &[u32]) -> [u8; 16] {
+    let mut state = state_from_block(block);
+
+    add_round_key(&mut state, w[4 * n_r..=4 * n_r + 3].try_into().unwrap());
+
+    for round in (1..n_r).rev() {
         inv_shift_rows(&mut state);
         inv_sub_bytes(&mut state);
         add_round_key(&mut state, w[4 * round..=4 * round + 3].try_into().unwrap());
@@ -32,23 +112,28 @@ pub fn inv_cypher(block: [u8; 16], n_r: usize, w: &[u32]) -> [u8; 16] {
     inv_sub_bytes(&mut state);
     add_round_key(&mut state, w[0..=3].try_into().unwrap());
 
-    state
-        .map(|x| x.to_ne_bytes())
-        .iter()
-        .flatten()
-        .copied()
-        .collect::<Vec<u8>>()
-        .try_into()
-        .unwrap()
+    block_from_state(state)
 }
 
-fn add_round_key(state: &mut State, w_round: State) {
+pub(crate) fn add_round_key(state: &mut State, w_round: State) {
+    // key schedule words are big-endian, state columns are little-endian
     for i in 0..=3 {
-        state[i] ^= w_round[i];
+        state[i] ^= w_round[i].swap_bytes();
     }
 }
 
-fn inv_shift_rows(state: &mut State) {
+pub(crate) fn shift_rows(state: &mut State) {
+    let bytes: [[u8; 4]; 4] = state.map(|x| x.to_le_bytes());
+
+    *state = [
+        u32::from_le_bytes([bytes[0][0], bytes[1][1], bytes[2][2], bytes[3][3]]),
+        u32::from_le_bytes([bytes[1][0], bytes[2][1], bytes[3][2], bytes[0][3]]),
+        u32::from_le_bytes([bytes[2][0], bytes[3][1], bytes[0][2], bytes[1][3]]),
+        u32::from_le_bytes([bytes[3][0], bytes[0][1], bytes[1][2], bytes[2][3]]),
+    ];
+}
+
+pub(crate) fn inv_shift_rows(state: &mut State) {
     let bytes: [[u8; 4]; 4] = state.map(|x| x.to_le_bytes());
 
     // little-endian makes this code read like the spec
@@ -60,11 +145,15 @@ fn inv_shift_rows(state: &mut State) {
     ];
 }
 
-fn inv_sub_bytes(state: &mut State) {
+pub(crate) fn sub_bytes(state: &mut State) {
+    state.iter_mut().for_each(|x| *x = subword(*x));
+}
+
+pub(crate) fn inv_sub_bytes(state: &mut State) {
     state.iter_mut().for_each(|x| *x = inv_subword(*x));
 }
 
-fn mix_columns(state: &mut State) {
+pub(crate) fn mix_columns(state: &mut State) {
     state.iter_mut().for_each(|column| {
         // loop over columns
         let bytes = (*column).to_le_bytes();
@@ -77,7 +166,7 @@ fn mix_columns(state: &mut State) {
     });
 }
 
-fn inv_mix_columns(state: &mut State) {
+pub(crate) fn inv_mix_columns(state: &mut State) {
     state.iter_mut().for_each(|column| {
         // loop over columns
         let bytes = (*column).to_le_bytes();
@@ -103,7 +192,7 @@ fn inv_mix_columns(state: &mut State) {
 }
 
 // multiplication in GF(2^8)
-const fn xtimes(a: u8, b: u8) -> u8 {
+pub(crate) const fn xtimes(a: u8, b: u8) -> u8 {
     let mut a_mut = a;
     let mut b_mut = b;
     let mut result: u16 = 0;
@@ -140,20 +229,16 @@ const fn rotword(a: u32) -> u32 {
     a.rotate_left(8)
 }
 
+// the S-box maps 0 to its own "inverse"
 const fn inverse(a: u8) -> u8 {
-    let mut a_inv = a;
-    if a_inv != 0 {
-        let mut i = 0;
-        while i < 253 {
-            a_inv = xtimes(a_inv, a);
-            i += 1;
-        }
+    match Gf256::<AES_POLYNOMIAL>(a).checked_inv() {
+        Some(a_inv) => a_inv.0,
+        None => 0,
     }
-    a_inv
 }
 
-const SBOX: [u8; 256] = sbox_gen_all().0;
-const INV_SBOX: [u8; 256] = sbox_gen_all().1;
+pub const SBOX: [u8; 256] = sbox_gen_all().0;
+pub const INV_SBOX: [u8; 256] = sbox_gen_all().1;
 
 const fn sbox_gen_all() -> ([u8; 256], [u8; 256]) {
     let mut result = [0u8; 256];
@@ -198,25 +283,54 @@ const fn inv_subword(a: u32) -> u32 {
         | (INV_SBOX[(a & 0xff) as usize] as u32)
 }
 
-fn key_expansion(key: &[u8], n_k: usize, n_r: usize) -> Vec<u32> {
-    let mut w = vec![0u32; n_k * (n_r + 1)];
-    for i in 0..n_k {
-        for j in 0..n_k {
-            w[i] |= (key[i * 4 + j] as u32) << ((n_k - 1 - j) * 8);
-        }
+pub fn key_expansion(key: &[u8], n_k: usize, n_r: usize) -> Vec<u32> {
+    assert_eq!(key.len(), 4 * n_k);
+    let mut w = vec![0u32; 4 * (n_r + 1)];
+    for (i, word) in key.chunks_exact(4).enumerate() {
+        w[i] = u32::from_be_bytes(word.try_into().unwrap());
     }
-    for i in n_k..=4 * n_r + 3 {
-        let mut temp = w[i - 1];
-        if i % n_k == 0 {
-            temp = subword(rotword(temp)) ^ RCON[i / n_k];
-        } else if (n_k > 6) && (i % n_k == 4) {
-            temp = subword(temp)
-        }
-        w[i] = w[i - n_k] ^ temp;
+    for i in n_k..w.len() {
+        w[i] = w[i - n_k] ^ schedule_temp(w[i - 1], i, n_k);
     }
     w
 }
 
+// the value xored into w[i - n_k] to produce w[i]
+pub(crate) const fn schedule_temp(prev: u32, i: usize, n_k: usize) -> u32 {
+    if i.is_multiple_of(n_k) {
+        subword(rotword(prev)) ^ RCON[i / n_k]
+    } else if (n_k > 6) && (i % n_k == 4) {
+        subword(prev)
+    } else {
+        prev
+    }
+}
+
+// Run the key schedule backwards: given n_k consecutive words starting at
+// word index first_word, recover the cypher key. w[i - n_k] only depends on
+// w[i] and w[i - 1], so each step down needs nothing outside the window.
+pub fn inv_key_expansion(words: &[u32], first_word: usize, n_k: usize) -> Vec<u8> {
+    assert!(words.len() >= n_k);
+    let mut w = vec![0u32; first_word + n_k];
+    w[first_word..].copy_from_slice(&words[..n_k]);
+    for i in (n_k..first_word + n_k).rev() {
+        w[i - n_k] = w[i] ^ schedule_temp(w[i - 1], i, n_k);
+    }
+    w[..n_k].iter().flat_map(|x| x.to_be_bytes()).collect()
+}
+
+// Recover the cypher key from round key `round` as bytes. AES-128 needs just
+// that round key, AES-192 and AES-256 need the following round key as well
+// (24 or 32 bytes starting at the round key) since n_k > 4.
+pub fn key_from_round_key(round_keys: &[u8], round: usize, n_k: usize) -> Vec<u8> {
+    assert!(round_keys.len() >= 4 * n_k);
+    let words: Vec<u32> = round_keys[..4 * n_k]
+        .chunks_exact(4)
+        .map(|x| u32::from_be_bytes(x.try_into().unwrap()))
+        .collect();
+    inv_key_expansion(&words, 4 * round, n_k)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -233,8 +347,8 @@ mod test {
             assert_ne!(val, i);
             found[SBOX[i] as usize] += 1;
         }
-        for i in 0..=0xff {
-            assert_eq!(found[i], 1);
+        for count in found {
+            assert_eq!(count, 1);
         }
     }
 
@@ -254,6 +368,46 @@ mod test {
         assert_eq_hex!(key_expansion(&key, key.len() / 4, 10), expanded);
     }
 
+    #[test]
+    // last round keys from Appendix A of FIPS 197
+    fn test_inv_key_expansion() {
+        let key = Vec::from_hex_byte_array(b"2b7e151628aed2a6abf7158809cf4f3c").unwrap();
+        let last = [0xd014f9a8, 0xc9ee2589, 0xe13f0cc8, 0xb6630ca6];
+        assert_eq_hex!(inv_key_expansion(&last, 40, 4), key);
+        let w = key_expansion(&key, 4, 10);
+        for round in 0..=10 {
+            assert_eq_hex!(inv_key_expansion(&w[4 * round..], 4 * round, 4), key);
+        }
+
+        let key =
+            Vec::from_hex_byte_array(b"8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b").unwrap();
+        let last = [
+            0xca400538, 0x8fcc5006, 0x282d166a, 0xbc3ce7b5, 0xe98ba06f, 0x448c773c, 0x8ecc7204,
+            0x01002202,
+        ];
+        assert_eq_hex!(inv_key_expansion(&last[2..], 46, 6), key);
+        let round_keys: Vec<u8> = last.iter().flat_map(|x| x.to_be_bytes()).collect();
+        assert_eq_hex!(key_from_round_key(&round_keys, 11, 6), key);
+
+        let key = Vec::from_hex_byte_array(
+            b"603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4",
+        )
+        .unwrap();
+        let last = [
+            0xcafaaae3, 0xe4d59b34, 0x9adf6ace, 0xbd10190d, 0xfe4890d1, 0xe6188d0b, 0x046df344,
+            0x706c631e,
+        ];
+        assert_eq_hex!(inv_key_expansion(&last, 52, 8), key);
+        let w = key_expansion(&key, 8, 14);
+        for round in 0..=12 {
+            let round_keys: Vec<u8> = w[4 * round..4 * round + 8]
+                .iter()
+                .flat_map(|x| x.to_be_bytes())
+                .collect();
+            assert_eq_hex!(key_from_round_key(&round_keys, round, 8), key);
+        }
+    }
+
     #[test]
     fn test_inv_shift_rows() {
         let mut input = [0x30201000, 0x31211101, 0x32221202, 0x33231303];
@@ -261,6 +415,15 @@ mod test {
         assert_eq_hex!(input, [0x31221300, 0x32231001, 0x33201102, 0x30211203],)
     }
 
+    #[test]
+    fn test_shift_rows() {
+        let mut input = [0x30201000, 0x31211101, 0x32221202, 0x33231303];
+        let output = input;
+        shift_rows(&mut input);
+        inv_shift_rows(&mut input);
+        assert_eq_hex!(input, output,)
+    }
+
     #[test]
     fn test_mix_columns() {
         let mut input = [0x12345678, 0x00000000, 0xffffffff, 0x10101010];
@@ -270,6 +433,39 @@ mod test {
         assert_eq_hex!(input, output,)
     }
 
+    #[test]
+    // Appendix C.1 of FIPS 197
+    fn test_aes_128_ecb_encrypt() {
+        let key = Vec::from_hex_byte_array(b"000102030405060708090a0b0c0d0e0f").unwrap();
+        let plaintext = Vec::from_hex_byte_array(b"00112233445566778899aabbccddeeff").unwrap();
+        let cyphertext = encrypt_aes_128_ecb(&key, &plaintext);
+        assert_eq_hex!(
+            cyphertext,
+            Vec::from_hex_byte_array(b"69c4e0d86a7b0430d8cdb78070b4c55a").unwrap()
+        );
+        assert_eq_hex!(decrypt_aes_128_ecb(&key, &cyphertext), plaintext);
+    }
+
+    #[test]
+    // F.2.1 of NIST SP 800-38A
+    fn test_aes_128_cbc() {
+        let key = Vec::from_hex_byte_array(b"2b7e151628aed2a6abf7158809cf4f3c").unwrap();
+        let iv = Vec::from_hex_byte_array(b"000102030405060708090a0b0c0d0e0f").unwrap();
+        let plaintext = Vec::from_hex_byte_array(
+            b"6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51",
+        )
+        .unwrap();
+        let cyphertext = encrypt_aes_128_cbc(&key, &iv, &plaintext);
+        assert_eq_hex!(
+            cyphertext,
+            Vec::from_hex_byte_array(
+                b"7649abac8119b246cee98e9b12e9197d5086cb9b507219ee95db113a917678b2"
+            )
+            .unwrap()
+        );
+        assert_eq_hex!(decrypt_aes_128_cbc(&key, &iv, &cyphertext), plaintext);
+    }
+
     #[test]
     fn test_aes_128_ecb_decrypt() {
         let cyphertext = Vec::from_base64_byte_array(
@@ -285,9 +481,10 @@ mod test {
         assert_eq_hex!(
             decrypt.as_slice(),
             b"I know you wanted me to stay\n\
-    But I can't ignore the crazy visions of me in LA\n\
-    And I heard that there's a special place\n\
-    Where boys and girls can all be queens every single day\n"
+    But I can't ignore the crazy visions of me\xe2\x80\x85in\xe2\x80\x85LA\n\
+    And I heard\xe2\x80\x85that there's a special place\n\
+    Where boys\xe2\x80\x85and girls can all be queens every single day\n\
+    \x09\x09\x09\x09\x09\x09\x09\x09\x09"
         )
     }
 }
@@ -1,7 +1,7 @@
 use std::{collections::BTreeMap, fs::File, io::Read};
 
 use cryptopals::{
-    bytes::{xor_byte_array, ParseBytes},
+    bytes::{transpose_by_block, xor_byte_array, ParseBytes},
     distance::hamming_distance,
     frequency::{break_single_byte_xor, distance_metric},
 };
@@ -38,20 +38,6 @@ fn main() -> Result<(), std::io::Error> {
         );
     }
 
-    fn transpose_by_block(block_size: usize, data: &[u8]) -> Vec<Vec<u8>> {
-        let mut result = vec![];
-        for offset in 0..block_size {
-            let mut slice = vec![];
-            let mut ptr = 0;
-            while ptr + offset < data.len() {
-                slice.push(data[ptr + offset]);
-                ptr += block_size;
-            }
-            result.push(slice);
-        }
-        result
-    }
-
     let candidates: Vec<(f32, Vec<u8>, Vec<u8>)> = (0..5)
         .map(|_| {
             let (_, key_size) = key_size_scores.pop_first().expect("didn't find a key_size");
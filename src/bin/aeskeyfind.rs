@@ -0,0 +1,29 @@
+use std::{fs::File, io::Read};
+
+use cryptopals::{bytes::RenderBytes, keyfind::find_keys};
+
+// Scan a memory image for AES key schedules, tolerating some decayed bits
+
+fn main() -> Result<(), std::io::Error> {
+    let filename = std::env::args()
+        .nth(1)
+        .expect("requires a memory image to read");
+    let max_bit_errors: usize = std::env::args()
+        .nth(2)
+        .map(|x| x.parse().expect("bit error limit should be a number"))
+        .unwrap_or(16);
+    let mut file = File::open(filename)?;
+    let mut dump = vec![];
+    file.read_to_end(&mut dump)?;
+
+    for found in find_keys(&dump, max_bit_errors) {
+        println!(
+            "{:08x} AES-{} {} ({} bit errors)",
+            found.offset,
+            found.key.len() * 8,
+            String::from_utf8_lossy(&found.key.to_hex_byte_vec()),
+            found.bit_errors
+        );
+    }
+    Ok(())
+}
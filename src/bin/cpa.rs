@@ -0,0 +1,23 @@
+use cryptopals::power::{traces_needed, LeakageModel};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+// Correlation power analysis against simulated AES first round traces:
+// how many traces it takes to recover the whole key at each noise level
+
+fn main() {
+    let seed: u64 = std::env::args()
+        .nth(1)
+        .map(|x| x.parse().expect("seed should be a number"))
+        .unwrap_or(0);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let key: [u8; 16] = rng.gen();
+
+    for model in [LeakageModel::HammingWeight, LeakageModel::HammingDistance] {
+        for sigma in [0.0, 0.5, 1.0, 2.0, 4.0, 8.0] {
+            match traces_needed(&key, model, sigma, 50, 2000, &mut rng) {
+                Some(n) => println!("{:?} sigma {}: {} traces", model, sigma, n),
+                None => println!("{:?} sigma {}: more than 2000 traces", model, sigma),
+            }
+        }
+    }
+}
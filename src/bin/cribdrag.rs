@@ -0,0 +1,142 @@
+use std::io::{BufRead, Write};
+
+use cryptopals::cribdrag::{parse_cyphertexts, Session};
+
+// Interactive crib dragging over cyphertexts that share a keystream, one
+// per line in hex or base64
+
+const HELP: &str = "\
+show                       print every message as far as it's known
+drag <row> <crib>          try crib at each column of message row
+place <row> <column> <crib>  fix the keystream so message row reads crib there
+fix <column> <hex byte>    set one keystream byte
+unfix <column>             forget one keystream byte
+suggest <column>           key bytes for a column, best first
+auto                       fill unknown bytes with statistical guesses
+save <file>                write the keystream out
+load <file>                read a saved keystream back
+quit";
+
+fn show(session: &Session) {
+    for row in 0..session.cyphertexts.len() {
+        println!("{:3} {}", row, session.render(row));
+    }
+}
+
+fn printable(text: &[u8]) -> String {
+    text.iter()
+        .map(|x| match x {
+            x if x.is_ascii_graphic() || *x == b' ' => *x as char,
+            _ => '.',
+        })
+        .collect()
+}
+
+// one command; errors are reported as text and the session carries on
+fn run(session: &mut Session, line: &str) -> Result<(), String> {
+    let mut words = line.splitn(2, ' ');
+    let command = words.next().unwrap_or("");
+    let rest = words.next().unwrap_or("");
+    let number = |x: Option<&str>| -> Result<usize, String> {
+        x.ok_or("missing argument")?
+            .parse()
+            .map_err(|_| "expected a number".to_string())
+    };
+    match command {
+        "" => {}
+        "help" => println!("{}", HELP),
+        "show" => show(session),
+        "drag" => {
+            let (row, crib) = rest.split_once(' ').ok_or("drag <row> <crib>")?;
+            let row = number(Some(row))?;
+            if row >= session.cyphertexts.len() {
+                return Err("no such message".to_string());
+            }
+            for placement in session.drag(row, crib.as_bytes()).iter().take(10) {
+                println!("column {} score {:.3}", placement.column, placement.score);
+                for (other, text) in &placement.decryptions {
+                    println!("  {:3} {}", other, printable(text));
+                }
+            }
+        }
+        "place" => {
+            let mut args = rest.splitn(3, ' ');
+            let row = number(args.next())?;
+            let column = number(args.next())?;
+            let crib = args.next().ok_or("place <row> <column> <crib>")?;
+            session
+                .place(row, column, crib.as_bytes())
+                .map_err(|e| format!("{:?}", e))?;
+            show(session);
+        }
+        "fix" => {
+            let mut args = rest.split_whitespace();
+            let column = number(args.next())?;
+            let key = u8::from_str_radix(args.next().ok_or("fix <column> <hex byte>")?, 16)
+                .map_err(|_| "expected a hex byte")?;
+            session.fix(column, key).map_err(|e| format!("{:?}", e))?;
+            show(session);
+        }
+        "unfix" => {
+            session
+                .unfix(number(Some(rest.trim()))?)
+                .map_err(|e| format!("{:?}", e))?;
+            show(session);
+        }
+        "suggest" => {
+            let column = number(Some(rest.trim()))?;
+            if column >= session.keystream.len() {
+                return Err("column out of range".to_string());
+            }
+            for (key, score) in session.suggest(column).iter().take(10) {
+                let column_text: Vec<u8> = session
+                    .cyphertexts
+                    .iter()
+                    .filter(|x| x.len() > column)
+                    .map(|x| x[column] ^ key)
+                    .collect();
+                println!("{:02x} {:.3} {}", key, score, printable(&column_text));
+            }
+        }
+        "auto" => {
+            session.fill_unknown();
+            show(session);
+        }
+        "save" => std::fs::write(rest.trim(), session.save() + "\n").map_err(|e| e.to_string())?,
+        "load" => {
+            let saved = std::fs::read_to_string(rest.trim()).map_err(|e| e.to_string())?;
+            session.restore(&saved).map_err(|e| format!("{:?}", e))?;
+            show(session);
+        }
+        _ => return Err(format!("unknown command {}, try help", command)),
+    }
+    Ok(())
+}
+
+fn main() -> Result<(), std::io::Error> {
+    let filename = std::env::args()
+        .nth(1)
+        .expect("requires a file of cyphertexts");
+    let cyphertexts = parse_cyphertexts(&std::fs::read_to_string(filename)?)
+        .unwrap_or_else(|e| panic!("can't read cyphertexts: {:?}", e));
+    let mut session = Session::new(cyphertexts);
+    show(&session);
+
+    let stdin = std::io::stdin();
+    loop {
+        print!("> ");
+        std::io::stdout().flush()?;
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            break;
+        }
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line == "quit" {
+            break;
+        }
+        if let Err(e) = run(&mut session, line) {
+            println!("{}", e);
+        }
+    }
+    Ok(())
+}
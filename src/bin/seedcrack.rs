@@ -0,0 +1,29 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use cryptopals::seed_crack::{available_threads, crack_time_seed};
+
+// Find the UNIX time an MT19937 was seeded with from its first outputs,
+// searching back from now
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let seconds_back: u32 = args
+        .next()
+        .expect("requires how many seconds back to search, then outputs")
+        .parse()
+        .expect("seconds should be a number");
+    let outputs: Vec<u32> = args
+        .map(|x| x.parse().expect("outputs should be numbers"))
+        .collect();
+    assert!(!outputs.is_empty(), "requires at least one output");
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("clock before 1970")
+        .as_secs() as u32;
+    let window = now.saturating_sub(seconds_back)..now + 1;
+    match crack_time_seed(&outputs, window, available_threads()) {
+        Some(seed) => println!("seed {} ({} seconds ago)", seed, now - seed),
+        None => println!("no seed in the last {} seconds", seconds_back),
+    }
+}
@@ -0,0 +1,175 @@
+use rand::Rng;
+
+use crate::aes::{decrypt_aes_128_cbc, encrypt_aes_128_cbc};
+use crate::bytes::{pkcs7_pad, pkcs7_unpad, xor_byte_array};
+use crate::ctr::aes_128_ctr;
+use crate::oracle::Oracle;
+
+// Turning known plaintext into chosen plaintext by editing cyphertext.
+
+fn flip(cyphertext: &[u8], position: usize, known: &[u8], desired: &[u8]) -> Vec<u8> {
+    assert_eq!(known.len(), desired.len());
+    let mut edited = cyphertext.to_vec();
+    let difference = xor_byte_array(known, desired);
+    let target = &mut edited[position..position + difference.len()];
+    target.copy_from_slice(&xor_byte_array(target, &difference));
+    edited
+}
+
+// A CBC plaintext block is xored with the previous cyphertext block, so edit
+// that. The edited block decrypts to garbage, and plaintext before offset 16
+// would need the IV edited instead.
+pub fn cbc_bitflip(cyphertext: &[u8], offset: usize, known: &[u8], desired: &[u8]) -> Vec<u8> {
+    assert!(offset >= 16, "the first block needs the IV edited");
+    assert!(
+        (offset + known.len() - 1) / 16 == offset / 16,
+        "one block at a time"
+    );
+    flip(cyphertext, offset - 16, known, desired)
+}
+
+// CTR plaintext is xored straight into the cyphertext, nothing else changes
+pub fn ctr_bitflip(cyphertext: &[u8], offset: usize, known: &[u8], desired: &[u8]) -> Vec<u8> {
+    flip(cyphertext, offset, known, desired)
+}
+
+const COMMENT_PREFIX: &[u8] = b"comment1=cooking%20MCs;userdata=";
+const COMMENT_SUFFIX: &[u8] = b";comment2=%20like%20a%20pound%20of%20bacon";
+
+// exercises 16 and 26 quote ; and = so userdata can't add fields directly
+pub fn wrap_userdata(userdata: &str) -> Vec<u8> {
+    let quoted = userdata.replace(';', "%3B").replace('=', "%3D");
+    let mut plaintext = COMMENT_PREFIX.to_vec();
+    plaintext.extend_from_slice(quoted.as_bytes());
+    plaintext.extend_from_slice(COMMENT_SUFFIX);
+    plaintext
+}
+
+// bytes rather than a string, since a bitflipped block won't be UTF-8
+pub fn has_admin(plaintext: &[u8]) -> bool {
+    plaintext
+        .split(|x| *x == b';')
+        .any(|field| field == b"admin=true")
+}
+
+// Exercise 16
+pub struct CbcCommentService {
+    key: [u8; 16],
+    iv: [u8; 16],
+}
+
+impl CbcCommentService {
+    pub fn new(rng: &mut impl Rng) -> Self {
+        CbcCommentService {
+            key: rng.gen(),
+            iv: rng.gen(),
+        }
+    }
+
+    pub fn encrypt(&self, userdata: &str) -> Vec<u8> {
+        encrypt_aes_128_cbc(
+            &self.key,
+            &self.iv,
+            &pkcs7_pad(&wrap_userdata(userdata), 16),
+        )
+    }
+
+    pub fn is_admin(&self, cyphertext: &[u8]) -> bool {
+        if !cyphertext.len().is_multiple_of(16) {
+            return false;
+        }
+        let padded = decrypt_aes_128_cbc(&self.key, &self.iv, cyphertext);
+        pkcs7_unpad(&padded, 16).is_ok_and(|plaintext| has_admin(&plaintext))
+    }
+}
+
+impl Oracle for CbcCommentService {
+    type Input = str;
+    type Output = Vec<u8>;
+    fn query(&mut self, userdata: &str) -> Vec<u8> {
+        self.encrypt(userdata)
+    }
+}
+
+// Exercise 26
+pub struct CtrCommentService {
+    key: [u8; 16],
+    nonce: u64,
+}
+
+impl CtrCommentService {
+    pub fn new(rng: &mut impl Rng) -> Self {
+        CtrCommentService {
+            key: rng.gen(),
+            nonce: rng.gen(),
+        }
+    }
+
+    pub fn encrypt(&self, userdata: &str) -> Vec<u8> {
+        aes_128_ctr(&self.key, self.nonce, &wrap_userdata(userdata))
+    }
+
+    pub fn is_admin(&self, cyphertext: &[u8]) -> bool {
+        has_admin(&aes_128_ctr(&self.key, self.nonce, cyphertext))
+    }
+}
+
+impl Oracle for CtrCommentService {
+    type Input = str;
+    type Output = Vec<u8>;
+    fn query(&mut self, userdata: &str) -> Vec<u8> {
+        self.encrypt(userdata)
+    }
+}
+
+// harmless stand-ins for ; and =, one bit away from the real thing
+const KNOWN: &[u8] = b":admin<true";
+const DESIRED: &[u8] = b";admin=true";
+
+pub fn cbc_make_admin(oracle: &mut impl Oracle<Input = str, Output = Vec<u8>>) -> Vec<u8> {
+    // the prefix is exactly two blocks, so a block of filler to sacrifice
+    // and then the block to rewrite
+    let filler = "A".repeat(16);
+    let userdata = format!("{}{}", filler, std::str::from_utf8(KNOWN).unwrap());
+    let cyphertext = oracle.query(&userdata);
+    cbc_bitflip(&cyphertext, COMMENT_PREFIX.len() + 16, KNOWN, DESIRED)
+}
+
+pub fn ctr_make_admin(oracle: &mut impl Oracle<Input = str, Output = Vec<u8>>) -> Vec<u8> {
+    let cyphertext = oracle.query(std::str::from_utf8(KNOWN).unwrap());
+    ctr_bitflip(&cyphertext, COMMENT_PREFIX.len(), KNOWN, DESIRED)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_quoting() {
+        let mut rng = StdRng::seed_from_u64(16);
+        let cbc = CbcCommentService::new(&mut rng);
+        let ctr = CtrCommentService::new(&mut rng);
+        assert!(!cbc.is_admin(&cbc.encrypt(";admin=true;")));
+        assert!(!ctr.is_admin(&ctr.encrypt(";admin=true;")));
+        assert!(!cbc.is_admin(&[0u8; 20]));
+    }
+
+    // Exercise 16 solution
+    #[test]
+    fn test_cbc_bitflip() {
+        let mut rng = StdRng::seed_from_u64(16);
+        let mut service = CbcCommentService::new(&mut rng);
+        let forged = cbc_make_admin(&mut service);
+        assert!(service.is_admin(&forged));
+    }
+
+    // Exercise 26 solution
+    #[test]
+    fn test_ctr_bitflip() {
+        let mut rng = StdRng::seed_from_u64(26);
+        let mut service = CtrCommentService::new(&mut rng);
+        let forged = ctr_make_admin(&mut service);
+        assert!(service.is_admin(&forged));
+    }
+}
@@ -2,6 +2,8 @@
 pub struct HexParseError; // make this more useful
 #[derive(Clone, Copy, Debug)]
 pub struct Base64ParseError;
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PaddingError;
 
 fn hex_u8_to_u8(x: u8) -> Result<u8, HexParseError> {
     let is_letter = ((b'A'..=b'F').contains(&x) | (b'a'..=b'f').contains(&x)) as u8;
@@ -60,7 +62,7 @@ impl ParseBytes for Vec<u8> {
             .filter(|x| BASE64_SYMBOLS.contains(x) || **x == b'=')
             .copied()
             .collect();
-        if src_filtered.len() % 4 != 0 {
+        if !src_filtered.len().is_multiple_of(4) {
             return Err(Base64ParseError);
         }
         let mut result: Vec<u8> = Vec::with_capacity(src_filtered.len() * 3 / 4);
@@ -124,6 +126,22 @@ pub fn xor_byte_array(message: &[u8], key: &[u8]) -> Vec<u8> {
         .collect()
 }
 
+// column i holds every byte at an offset congruent to i mod block_size, so
+// each column was xored with the same key byte
+pub fn transpose_by_block(block_size: usize, data: &[u8]) -> Vec<Vec<u8>> {
+    let mut result = vec![];
+    for offset in 0..block_size {
+        let mut slice = vec![];
+        let mut ptr = 0;
+        while ptr + offset < data.len() {
+            slice.push(data[ptr + offset]);
+            ptr += block_size;
+        }
+        result.push(slice);
+    }
+    result
+}
+
 // Exercise 9 solution
 pub fn pkcs7_pad(message: &[u8], block_size: usize) -> Vec<u8> {
     assert!(block_size <= 256);
@@ -134,6 +152,22 @@ pub fn pkcs7_pad(message: &[u8], block_size: usize) -> Vec<u8> {
     padded_message
 }
 
+// Exercise 15 solution
+pub fn pkcs7_unpad(message: &[u8], block_size: usize) -> Result<Vec<u8>, PaddingError> {
+    if message.is_empty() || !message.len().is_multiple_of(block_size) {
+        return Err(PaddingError);
+    }
+    let padding_size = *message.last().unwrap() as usize;
+    if padding_size == 0 || padding_size > block_size {
+        return Err(PaddingError);
+    }
+    let (unpadded, padding) = message.split_at(message.len() - padding_size);
+    if padding.iter().any(|x| *x as usize != padding_size) {
+        return Err(PaddingError);
+    }
+    Ok(unpadded.to_vec())
+}
+
 #[cfg(test)]
 mod tests {
     use assert_hex::assert_eq_hex;
@@ -243,6 +277,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_transpose_by_block() {
+        assert_eq!(
+            transpose_by_block(3, b"abcdefgh"),
+            vec![b"adg".to_vec(), b"beh".to_vec(), b"cf".to_vec()]
+        );
+    }
+
     // Exercise 9
     #[test]
     fn test_pkcs7_pad() {
@@ -251,4 +293,26 @@ mod tests {
             b"YELLOW SUBMARINE\x04\x04\x04\x04"
         );
     }
+
+    // Exercise 15
+    #[test]
+    fn test_pkcs7_unpad() {
+        assert_eq!(
+            pkcs7_unpad(b"ICE ICE BABY\x04\x04\x04\x04", 16).unwrap(),
+            b"ICE ICE BABY"
+        );
+        assert_eq!(
+            pkcs7_unpad(b"ICE ICE BABY\x05\x05\x05\x05", 16),
+            Err(PaddingError)
+        );
+        assert_eq!(
+            pkcs7_unpad(b"ICE ICE BABY\x01\x02\x03\x04", 16),
+            Err(PaddingError)
+        );
+        assert_eq!(pkcs7_unpad(b"ICE ICE BABY\x00", 13), Err(PaddingError));
+        assert_eq!(
+            pkcs7_unpad(&pkcs7_pad(b"YELLOW SUBMARINE", 16), 16).unwrap(),
+            b"YELLOW SUBMARINE"
+        );
+    }
 }
@@ -0,0 +1,113 @@
+use rand::Rng;
+
+use crate::aes::{decrypt_aes_128_cbc, encrypt_aes_128_cbc};
+use crate::bitflip::{has_admin, wrap_userdata};
+use crate::bytes::{pkcs7_pad, pkcs7_unpad, xor_byte_array};
+use crate::oracle::Oracle;
+
+// Exercise 27: CBC with the key reused as the IV, behind a receiver that
+// complains about non-ASCII plaintext and quotes it back.
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum KeyIvError {
+    HighAscii(Vec<u8>),
+    BadPadding,
+    BadLength,
+}
+
+pub struct KeyAsIvService {
+    key: [u8; 16],
+}
+
+impl KeyAsIvService {
+    pub fn new(rng: &mut impl Rng) -> Self {
+        KeyAsIvService { key: rng.gen() }
+    }
+
+    pub fn encrypt(&self, userdata: &str) -> Vec<u8> {
+        let plaintext = pkcs7_pad(&wrap_userdata(userdata), 16);
+        encrypt_aes_128_cbc(&self.key, &self.key, &plaintext)
+    }
+
+    // the ASCII check comes before unpadding, so any cyphertext at all
+    // gets its decryption quoted back
+    pub fn decrypt(&self, cyphertext: &[u8]) -> Result<Vec<u8>, KeyIvError> {
+        if !cyphertext.len().is_multiple_of(16) {
+            return Err(KeyIvError::BadLength);
+        }
+        let padded = decrypt_aes_128_cbc(&self.key, &self.key, cyphertext);
+        if padded.iter().any(|x| *x >= 0x80) {
+            return Err(KeyIvError::HighAscii(padded));
+        }
+        pkcs7_unpad(&padded, 16).map_err(|_| KeyIvError::BadPadding)
+    }
+}
+
+impl Oracle for KeyAsIvService {
+    type Input = [u8];
+    type Output = Result<bool, KeyIvError>;
+    fn query(&mut self, cyphertext: &[u8]) -> Result<bool, KeyIvError> {
+        self.decrypt(cyphertext)
+            .map(|plaintext| has_admin(&plaintext))
+    }
+}
+
+// With C1 || 0 || C1, P1 is C1 decrypted against the key and P3 is the same
+// decrypted against zero, so P1 xor P3 is the key. Needs the receiver to
+// quote the plaintext back, which random P3 all but guarantees.
+pub fn recover_key(
+    oracle: &mut impl Oracle<Input = [u8], Output = Result<bool, KeyIvError>>,
+    cyphertext: &[u8],
+) -> Option<[u8; 16]> {
+    assert!(cyphertext.len() >= 48, "needs three blocks");
+    let c1 = &cyphertext[..16];
+    let mut forged = [c1, &[0u8; 16], c1].concat();
+    forged.extend_from_slice(&cyphertext[48..]);
+    match oracle.query(&forged) {
+        Err(KeyIvError::HighAscii(plaintext)) => {
+            xor_byte_array(&plaintext[..16], &plaintext[32..48])
+                .try_into()
+                .ok()
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_ascii_check() {
+        let mut rng = StdRng::seed_from_u64(27);
+        let mut service = KeyAsIvService::new(&mut rng);
+        let cyphertext = service.encrypt("some data");
+        assert_eq!(service.query(&cyphertext), Ok(false));
+        let mut garbled = cyphertext.clone();
+        garbled[20] ^= 0x80;
+        assert!(matches!(
+            service.query(&garbled),
+            Err(KeyIvError::HighAscii(_))
+        ));
+        assert_eq!(service.query(&cyphertext[..20]), Err(KeyIvError::BadLength));
+    }
+
+    // Exercise 27 solution
+    #[test]
+    fn test_recover_key() {
+        let mut rng = StdRng::seed_from_u64(27);
+        let mut service = KeyAsIvService::new(&mut rng);
+        let cyphertext = service.encrypt("");
+        let key = recover_key(&mut service, &cyphertext).unwrap();
+
+        // fresh traffic decrypts with the key, which is also the IV
+        let traffic = service.encrypt("more data");
+        let plaintext = pkcs7_unpad(&decrypt_aes_128_cbc(&key, &key, &traffic), 16).unwrap();
+        assert_eq!(plaintext, wrap_userdata("more data"));
+
+        // and anything can be forged
+        let forged = encrypt_aes_128_cbc(&key, &key, &pkcs7_pad(b"x;admin=true", 16));
+        assert_eq!(service.query(&forged), Ok(true));
+    }
+}
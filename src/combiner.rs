@@ -0,0 +1,171 @@
+use crate::distance::hamming_distance;
+use crate::lfsr::Lfsr;
+
+// Combination generators: several LFSRs run side by side, their output bits
+// fed through a boolean function. If the function's output agrees with one
+// input more often than not, that register can be attacked on its own.
+
+pub type CombiningFunction = fn(&[bool]) -> bool;
+
+// the first register picks which of the other two is output
+pub fn geffe(x: &[bool]) -> bool {
+    if x[0] {
+        x[1]
+    } else {
+        x[2]
+    }
+}
+
+pub struct Combiner {
+    pub registers: Vec<Lfsr>,
+    pub function: CombiningFunction,
+}
+
+impl Combiner {
+    pub fn new(registers: Vec<Lfsr>, function: CombiningFunction) -> Self {
+        Combiner {
+            registers,
+            function,
+        }
+    }
+
+    pub fn geffe(selector: Lfsr, first: Lfsr, second: Lfsr) -> Self {
+        Combiner::new(vec![selector, first, second], geffe)
+    }
+
+    pub fn next_bit(&mut self) -> bool {
+        let inputs: Vec<bool> = self.registers.iter_mut().map(|x| x.next_bit()).collect();
+        (self.function)(&inputs)
+    }
+}
+
+impl Iterator for Combiner {
+    type Item = bool;
+    fn next(&mut self) -> Option<bool> {
+        Some(self.next_bit())
+    }
+}
+
+// how often the output equals each input, over the whole truth table
+pub fn correlations(function: CombiningFunction, inputs: usize) -> Vec<f64> {
+    let rows = 1usize << inputs;
+    let mut agreements = vec![0usize; inputs];
+    for row in 0..rows {
+        let x: Vec<bool> = (0..inputs).map(|i| row >> i & 1 == 1).collect();
+        let output = function(&x);
+        for (agreement, input) in agreements.iter_mut().zip(&x) {
+            *agreement += (output == *input) as usize;
+        }
+    }
+    agreements.iter().map(|x| *x as f64 / rows as f64).collect()
+}
+
+// eight bits to a byte, first bit lowest, for hamming_distance
+pub fn pack_bits(bits: &[bool]) -> Vec<u8> {
+    bits.chunks(8)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .fold(0u8, |byte, (i, x)| byte | (*x as u8) << i)
+        })
+        .collect()
+}
+
+// fraction of bits the same
+pub fn agreement(a: &[bool], b: &[bool]) -> f64 {
+    assert_eq!(a.len(), b.len());
+    1.0 - hamming_distance(&pack_bits(a), &pack_bits(b)) as f64 / a.len() as f64
+}
+
+// Siegenthaler: run one register from every nonzero state and keep the one
+// whose output agrees with the keystream furthest from half the time. With
+// the agreement, which tells the guess from chance.
+pub fn correlation_attack(keystream: &[bool], taps: u64, length: u32) -> (u64, f64) {
+    assert!(length < 32, "too many states to try");
+    (1..1u64 << length)
+        .map(|state| {
+            let bits: Vec<bool> = Lfsr::new(state, taps, length)
+                .take(keystream.len())
+                .collect();
+            (state, agreement(keystream, &bits))
+        })
+        .max_by(|a, b| (a.1 - 0.5).abs().total_cmp(&(b.1 - 0.5).abs()))
+        .unwrap()
+}
+
+// Initial states of all three Geffe registers, given their taps and lengths.
+// The two selected registers leak through correlation; with them known the
+// selector is whatever reproduces the keystream exactly.
+pub fn recover_geffe(keystream: &[bool], registers: [(u64, u32); 3]) -> Option<[u64; 3]> {
+    let [(selector_taps, selector_length), first, second] = registers;
+    let (first_state, _) = correlation_attack(keystream, first.0, first.1);
+    let (second_state, _) = correlation_attack(keystream, second.0, second.1);
+    (1..1u64 << selector_length).find_map(|selector_state| {
+        let generator = Combiner::geffe(
+            Lfsr::new(selector_state, selector_taps, selector_length),
+            Lfsr::new(first_state, first.0, first.1),
+            Lfsr::new(second_state, second.0, second.1),
+        );
+        generator
+            .take(keystream.len())
+            .eq(keystream.iter().copied())
+            .then_some([selector_state, first_state, second_state])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // primitive trinomials x^L + x^k + 1, so s[n+L] = s[n+k] ^ s[n]
+    const SELECTOR: (u64, u32) = (1 << 2 | 1, 11);
+    const FIRST: (u64, u32) = (1 << 1 | 1, 15);
+    const SECOND: (u64, u32) = (1 << 3 | 1, 17);
+
+    #[test]
+    fn test_correlations() {
+        assert_eq!(correlations(geffe, 3), vec![0.5, 0.75, 0.75]);
+        // majority agrees with each input three times in four
+        let majority: CombiningFunction = |x| (x[0] as u8 + x[1] as u8 + x[2] as u8) >= 2;
+        assert_eq!(correlations(majority, 3), vec![0.75, 0.75, 0.75]);
+        // xor leaks nothing about any single input
+        let xor: CombiningFunction = |x| x[0] ^ x[1];
+        assert_eq!(correlations(xor, 2), vec![0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_agreement() {
+        assert_eq!(
+            pack_bits(&[true, false, true, true, false, false, false, false, true]),
+            vec![0b1101, 1]
+        );
+        assert_eq!(
+            agreement(&[true, false, true, true], &[true, true, true, false]),
+            0.5
+        );
+    }
+
+    #[test]
+    fn test_recover_geffe() {
+        let states = [0x5a5, 0x1234, 0x1abcd];
+        let generator = Combiner::geffe(
+            Lfsr::new(states[0], SELECTOR.0, SELECTOR.1),
+            Lfsr::new(states[1], FIRST.0, FIRST.1),
+            Lfsr::new(states[2], SECOND.0, SECOND.1),
+        );
+        let keystream: Vec<bool> = generator.take(256).collect();
+
+        // the selector's best guess is no better than chance, the others are
+        // near three in four
+        let (_, selector_agreement) = correlation_attack(&keystream, SELECTOR.0, SELECTOR.1);
+        let (first_state, first_agreement) = correlation_attack(&keystream, FIRST.0, FIRST.1);
+        assert_eq!(first_state, states[1]);
+        assert!(first_agreement > 0.7 && selector_agreement < 0.65);
+
+        assert_eq!(
+            recover_geffe(&keystream, [SELECTOR, FIRST, SECOND]),
+            Some(states)
+        );
+    }
+}
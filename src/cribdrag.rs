@@ -0,0 +1,312 @@
+use crate::bytes::ParseBytes;
+use crate::fixed_nonce::break_fixed_nonce;
+use crate::frequency::distance_metric;
+
+// Manual help for many-time pads, where scoring gets most of the keystream
+// but guesses badly wherever only a few messages are left. Keystream bytes
+// are unknown until fixed, by hand or from a crib placed in one message.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CribDragError {
+    BadCyphertext(usize), // line number, from 1
+    BadKeystream,
+    OutOfRange,
+}
+
+// one cyphertext per line, hex if it looks like hex and base64 otherwise;
+// blank lines are skipped
+pub fn parse_cyphertexts(text: &str) -> Result<Vec<Vec<u8>>, CribDragError> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let line = line.trim().as_bytes();
+            let parsed = if line.len() % 2 == 0 && line.iter().all(|x| x.is_ascii_hexdigit()) {
+                Vec::from_hex_byte_array(line).ok()
+            } else {
+                Vec::from_base64_byte_array(line).ok()
+            };
+            parsed.ok_or(CribDragError::BadCyphertext(i + 1))
+        })
+        .collect()
+}
+
+pub struct Placement {
+    pub column: usize,
+    pub score: f32,
+    // what the crib makes every other message read at that column
+    pub decryptions: Vec<(usize, Vec<u8>)>,
+}
+
+pub struct Session {
+    pub cyphertexts: Vec<Vec<u8>>,
+    pub keystream: Vec<Option<u8>>,
+}
+
+impl Session {
+    pub fn new(cyphertexts: Vec<Vec<u8>>) -> Self {
+        let longest = cyphertexts.iter().map(|x| x.len()).max().unwrap_or(0);
+        Session {
+            cyphertexts,
+            keystream: vec![None; longest],
+        }
+    }
+
+    // unknown bytes as None
+    pub fn plaintext(&self, row: usize) -> Vec<Option<u8>> {
+        self.cyphertexts[row]
+            .iter()
+            .zip(&self.keystream)
+            .map(|(c, k)| k.map(|k| c ^ k))
+            .collect()
+    }
+
+    // _ for unknown, . for anything unprintable
+    pub fn render(&self, row: usize) -> String {
+        self.plaintext(row)
+            .iter()
+            .map(|x| match x {
+                None => '_',
+                Some(x) if x.is_ascii_graphic() || *x == b' ' => *x as char,
+                Some(_) => '.',
+            })
+            .collect()
+    }
+
+    pub fn fix(&mut self, column: usize, key: u8) -> Result<(), CribDragError> {
+        *self
+            .keystream
+            .get_mut(column)
+            .ok_or(CribDragError::OutOfRange)? = Some(key);
+        Ok(())
+    }
+
+    pub fn unfix(&mut self, column: usize) -> Result<(), CribDragError> {
+        *self
+            .keystream
+            .get_mut(column)
+            .ok_or(CribDragError::OutOfRange)? = None;
+        Ok(())
+    }
+
+    // fix the keystream so that message row reads crib from column on
+    pub fn place(&mut self, row: usize, column: usize, crib: &[u8]) -> Result<(), CribDragError> {
+        let cyphertext = self.cyphertexts.get(row).ok_or(CribDragError::OutOfRange)?;
+        let window = cyphertext
+            .get(column..column + crib.len())
+            .ok_or(CribDragError::OutOfRange)?;
+        for (i, (c, p)) in window.iter().zip(crib).enumerate() {
+            self.keystream[column + i] = Some(c ^ p);
+        }
+        Ok(())
+    }
+
+    // Try the crib in message row at every column, ranked by how English
+    // everything else reads there. Doesn't change the keystream.
+    pub fn drag(&self, row: usize, crib: &[u8]) -> Vec<Placement> {
+        let cyphertext = &self.cyphertexts[row];
+        if crib.len() > cyphertext.len() {
+            return vec![];
+        }
+        let mut placements: Vec<Placement> = (0..=cyphertext.len() - crib.len())
+            .map(|column| {
+                let keystream: Vec<u8> = cyphertext[column..column + crib.len()]
+                    .iter()
+                    .zip(crib)
+                    .map(|(c, p)| c ^ p)
+                    .collect();
+                let decryptions: Vec<(usize, Vec<u8>)> = self
+                    .cyphertexts
+                    .iter()
+                    .enumerate()
+                    .filter(|(other, x)| *other != row && x.len() > column)
+                    .map(|(other, x)| {
+                        let end = x.len().min(column + crib.len());
+                        let decrypted = x[column..end]
+                            .iter()
+                            .zip(&keystream)
+                            .map(|(c, k)| c ^ k)
+                            .collect();
+                        (other, decrypted)
+                    })
+                    .collect();
+                let all: Vec<u8> = decryptions.iter().flat_map(|x| x.1.clone()).collect();
+                Placement {
+                    column,
+                    score: distance_metric(&all),
+                    decryptions,
+                }
+            })
+            .collect();
+        placements.sort_by(|a, b| a.score.total_cmp(&b.score));
+        placements
+    }
+
+    // Every key byte for a column, best first. Each message reaching the
+    // column is scored along with the known text just before it, so a guess
+    // has to fit mid-word as well as look like a letter.
+    pub fn suggest(&self, column: usize) -> Vec<(u8, f32)> {
+        const CONTEXT: usize = 8;
+        let contexts: Vec<(Vec<u8>, u8)> = (0..self.cyphertexts.len())
+            .filter(|row| self.cyphertexts[*row].len() > column)
+            .map(|row| {
+                let plaintext = self.plaintext(row);
+                let known: Vec<u8> = plaintext[column.saturating_sub(CONTEXT)..column]
+                    .iter()
+                    .rev()
+                    .map_while(|x| *x)
+                    .collect::<Vec<u8>>()
+                    .into_iter()
+                    .rev()
+                    .collect();
+                (known, self.cyphertexts[row][column])
+            })
+            .collect();
+        let mut suggestions: Vec<(u8, f32)> = (0x00..=0xff)
+            .map(|key| {
+                let text: Vec<u8> = contexts
+                    .iter()
+                    .flat_map(|(known, c)| known.iter().copied().chain([c ^ key]))
+                    .collect();
+                (key, distance_metric(&text))
+            })
+            .collect();
+        suggestions.sort_by(|a, b| a.1.total_cmp(&b.1));
+        suggestions
+    }
+
+    // statistical guesses for whatever isn't fixed yet
+    pub fn fill_unknown(&mut self) {
+        if self.cyphertexts.is_empty() {
+            return;
+        }
+        let guessed = break_fixed_nonce(&self.cyphertexts);
+        for (k, guess) in self.keystream.iter_mut().zip(guessed) {
+            k.get_or_insert(guess);
+        }
+    }
+
+    // keystream as hex, ?? for unknown bytes
+    pub fn save(&self) -> String {
+        self.keystream
+            .iter()
+            .map(|x| match x {
+                Some(x) => format!("{:02x}", x),
+                None => "??".to_string(),
+            })
+            .collect()
+    }
+
+    pub fn restore(&mut self, saved: &str) -> Result<(), CribDragError> {
+        let saved = saved.trim().as_bytes();
+        if saved.len() != 2 * self.keystream.len() {
+            return Err(CribDragError::BadKeystream);
+        }
+        self.keystream = saved
+            .chunks(2)
+            .map(|pair| match pair {
+                b"??" => Ok(None),
+                _ => Vec::from_hex_byte_array(pair)
+                    .map(|x| Some(x[0]))
+                    .map_err(|_| CribDragError::BadKeystream),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bytes::xor_byte_array;
+    use crate::ctr::aes_128_ctr;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    const KEYSTREAM: &[u8] =
+        b"\x3a\x91\x0c\x77\xe4\x5b\x28\xd6\x1f\xa3\x60\xc9\x84\x02\xbe\x47\x9d\x35\xf0\x6e";
+
+    fn session() -> Session {
+        let plaintexts: [&[u8]; 3] = [
+            b"attack at dawn",
+            b"the eagle has landed",
+            b"send more troops",
+        ];
+        Session::new(
+            plaintexts
+                .iter()
+                .map(|x| xor_byte_array(x, &KEYSTREAM[..x.len()]))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_parse_cyphertexts() {
+        assert_eq!(
+            parse_cyphertexts("49276d\n\nSSdt\n").unwrap(),
+            vec![b"I'm".to_vec(), b"I'm".to_vec()]
+        );
+        assert_eq!(
+            parse_cyphertexts("49276d\nnot base64!"),
+            Err(CribDragError::BadCyphertext(2))
+        );
+    }
+
+    #[test]
+    fn test_drag_and_place() {
+        let mut session = session();
+        let best = &session.drag(1, b" has ")[0];
+        assert_eq!(best.column, 9);
+        assert_eq!(
+            best.decryptions,
+            vec![(0, b" dawn".to_vec()), (2, b" troo".to_vec())]
+        );
+
+        session.place(1, 9, b" has ").unwrap();
+        assert_eq!(session.render(0), "_________ dawn");
+        assert_eq!(session.render(2), "_________ troo__");
+        assert_eq!(
+            session.place(0, 10, b"dawn!"),
+            Err(CribDragError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_suggest() {
+        let mut rng = StdRng::seed_from_u64(40);
+        let key: [u8; 16] = rng.gen();
+        let cyphertexts: Vec<Vec<u8>> = include_str!("../data/19.txt")
+            .lines()
+            .map(|x| aes_128_ctr(&key, 0, &Vec::from_base64_byte_array(x.as_bytes()).unwrap()))
+            .collect();
+        let keystream = aes_128_ctr(&key, 0, &[0u8; 38]);
+
+        // with the start solved, the next column is easy while plenty of
+        // lines still reach it
+        let mut session = Session::new(cyphertexts);
+        for (column, key) in keystream[..25].iter().enumerate() {
+            session.fix(column, *key).unwrap();
+        }
+        assert_eq!(session.suggest(25)[0].0, keystream[25]);
+        assert_eq!(session.render(3), "Eighteenth-century houses_");
+        assert_eq!(session.fix(38, 0), Err(CribDragError::OutOfRange));
+    }
+
+    #[test]
+    fn test_fill_unknown_empty() {
+        let mut session = Session::new(vec![]);
+        session.fill_unknown();
+        assert!(session.keystream.is_empty());
+    }
+
+    #[test]
+    fn test_save_restore() {
+        let mut session = session();
+        session.place(1, 0, b"the").unwrap();
+        let saved = session.save();
+        assert_eq!(&saved[..8], "3a910c??");
+        session.unfix(0).unwrap();
+        session.restore(&saved).unwrap();
+        assert_eq!(session.render(0), "att___________");
+        assert_eq!(session.restore("3a91"), Err(CribDragError::BadKeystream));
+    }
+}
@@ -0,0 +1,80 @@
+use crate::aes::{cypher, key_expansion};
+use crate::stream::StreamCypher;
+
+// AES-128 in CTR mode, with the counter block laid out as cryptopals does it:
+// 64 bit little-endian nonce, then 64 bit little-endian block count.
+pub struct Ctr {
+    w: Vec<u32>,
+    nonce: u64,
+}
+
+impl Ctr {
+    pub fn new(key: &[u8], nonce: u64) -> Self {
+        Ctr {
+            w: key_expansion(key, 4, 10),
+            nonce,
+        }
+    }
+
+    pub fn keystream_block(&self, counter: u64) -> [u8; 16] {
+        let mut block = [0u8; 16];
+        block[..8].copy_from_slice(&self.nonce.to_le_bytes());
+        block[8..].copy_from_slice(&counter.to_le_bytes());
+        cypher(block, 10, &self.w)
+    }
+}
+
+impl StreamCypher for Ctr {
+    // only the blocks covering the range are computed
+    fn keystream(&self, offset: usize, length: usize) -> Vec<u8> {
+        let first = offset / 16;
+        let last = (offset + length).div_ceil(16);
+        let blocks: Vec<u8> = (first..last)
+            .flat_map(|counter| self.keystream_block(counter as u64))
+            .collect();
+        let start = offset % 16;
+        blocks[start..start + length].to_vec()
+    }
+}
+
+// Encryption and decryption are the same operation.
+pub fn aes_128_ctr(key: &[u8], nonce: u64, data: &[u8]) -> Vec<u8> {
+    Ctr::new(key, nonce).apply(0, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bytes::ParseBytes;
+
+    // Exercise 18 solution
+    #[test]
+    fn test_aes_128_ctr() {
+        let cyphertext = Vec::from_base64_byte_array(
+            b"L77na/nrFsKvynd6HzOoG7GHTLXsTVu9qvY/2syLXzhPweyyMTJULu/6/kXX0KSvoOLSFQ==",
+        )
+        .unwrap();
+        let plaintext = aes_128_ctr(b"YELLOW SUBMARINE", 0, &cyphertext);
+        assert_eq!(
+            plaintext,
+            b"Yo, VIP Let's kick it Ice, Ice, baby Ice, Ice, baby "
+        );
+        assert_eq!(aes_128_ctr(b"YELLOW SUBMARINE", 0, &plaintext), cyphertext);
+    }
+
+    #[test]
+    fn test_ctr_seek_and_edit() {
+        let ctr = Ctr::new(b"YELLOW SUBMARINE", 0);
+        let plaintext = b"Yo, VIP Let's kick it Ice, Ice, baby Ice, Ice, baby ";
+        let mut cyphertext = ctr.apply(0, plaintext);
+        // a range straddling block boundaries decrypts on its own
+        assert_eq!(ctr.apply(13, &cyphertext[13..40]), plaintext[13..40]);
+        assert_eq!(ctr.apply(0, &[]), vec![]);
+
+        ctr.edit(&mut cyphertext, 22, b"Vanilla!");
+        assert_eq!(
+            ctr.apply(0, &cyphertext),
+            b"Yo, VIP Let's kick it Vanilla!, baby Ice, Ice, baby "
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use rand::Rng;
+
+use crate::bytes::xor_byte_array;
+use crate::ctr::Ctr;
+use crate::oracle::Oracle;
+use crate::stream::StreamCypher;
+
+// Exercise 25: a CTR encrypted disk that lets anyone rewrite part of it.
+
+pub struct Edit {
+    pub offset: usize,
+    pub newtext: Vec<u8>,
+}
+
+pub struct EditService {
+    ctr: Ctr,
+    cyphertext: Vec<u8>,
+}
+
+impl EditService {
+    pub fn new(plaintext: &[u8], rng: &mut impl Rng) -> Self {
+        let ctr = Ctr::new(&rng.gen::<[u8; 16]>(), rng.gen());
+        let cyphertext = ctr.apply(0, plaintext);
+        EditService { ctr, cyphertext }
+    }
+
+    pub fn cyphertext(&self) -> &[u8] {
+        &self.cyphertext
+    }
+}
+
+// answers with the whole cyphertext after the edit; an edit that doesn't
+// fit is ignored
+impl Oracle for EditService {
+    type Input = Edit;
+    type Output = Vec<u8>;
+    fn query(&mut self, edit: &Edit) -> Vec<u8> {
+        let end = edit.offset.checked_add(edit.newtext.len());
+        if end.is_some_and(|end| end <= self.cyphertext.len()) {
+            self.ctr
+                .edit(&mut self.cyphertext, edit.offset, &edit.newtext);
+        }
+        self.cyphertext.clone()
+    }
+}
+
+// Writing zeros over everything hands back the keystream itself, and the
+// cyphertext from before the edit xored with it is the plaintext.
+pub fn recover_plaintext(
+    oracle: &mut impl Oracle<Input = Edit, Output = Vec<u8>>,
+    cyphertext: &[u8],
+) -> Vec<u8> {
+    let keystream = oracle.query(&Edit {
+        offset: 0,
+        newtext: vec![0; cyphertext.len()],
+    });
+    xor_byte_array(cyphertext, &keystream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::Counting;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    // Exercise 25 solution
+    #[test]
+    fn test_recover_plaintext() {
+        let mut rng = StdRng::seed_from_u64(25);
+        let plaintext = include_bytes!("../data/19.txt");
+        let mut service = Counting::new(EditService::new(plaintext, &mut rng));
+        let cyphertext = service.inner.cyphertext().to_vec();
+        assert_eq!(recover_plaintext(&mut service, &cyphertext), plaintext);
+        assert_eq!(service.queries(), 1);
+    }
+
+    #[test]
+    fn test_edit_service() {
+        let mut rng = StdRng::seed_from_u64(25);
+        let mut service = EditService::new(b"the quick brown fox", &mut rng);
+        let before = service.cyphertext().to_vec();
+        let after = service.query(&Edit {
+            offset: 10,
+            newtext: b"red".to_vec(),
+        });
+        assert_eq!(after[..10], before[..10]);
+        assert_eq!(after[13..], before[13..]);
+        assert_ne!(after[10..13], before[10..13]);
+
+        for (offset, length) in [(17, 3), (19, 1), (usize::MAX, 1)] {
+            let rejected = service.query(&Edit {
+                offset,
+                newtext: vec![0; length],
+            });
+            assert_eq!(rejected, after);
+        }
+    }
+}
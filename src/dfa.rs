@@ -0,0 +1,205 @@
+use std::collections::HashSet;
+
+use crate::aes::{
+    add_round_key, block_from_state, inv_shift_rows, key_expansion, key_from_round_key,
+    mix_columns, shift_rows, state_from_block, sub_bytes, xtimes, State, INV_SBOX,
+};
+
+// Piret-Quisquater differential fault analysis of AES-128: a single byte
+// fault going into the round 9 MixColumns spreads to one column, and after
+// the last SubBytes, ShiftRows and AddRoundKey it touches four cyphertext
+// bytes in a pattern that pins down four bytes of the last round key.
+
+const N_K: usize = 4;
+const N_R: usize = 10;
+
+// xor `mask` into byte `row` of column `column`, ahead of the MixColumns in
+// round n_r - 1
+#[derive(Clone, Copy, Debug)]
+pub struct Fault {
+    pub column: usize,
+    pub row: usize,
+    pub mask: u8,
+}
+
+pub struct FaultyAes {
+    w: Vec<u32>,
+}
+
+impl FaultyAes {
+    pub fn new(key: &[u8]) -> Self {
+        FaultyAes {
+            w: key_expansion(key, N_K, N_R),
+        }
+    }
+
+    pub fn encrypt(&self, block: [u8; 16], fault: Option<Fault>) -> [u8; 16] {
+        let w = self.w.as_slice();
+        let mut state = state_from_block(block);
+
+        add_round_key(&mut state, w[0..=3].try_into().unwrap());
+
+        for round in 1..N_R {
+            sub_bytes(&mut state);
+            shift_rows(&mut state);
+            if let Some(fault) = fault.filter(|_| round == N_R - 1) {
+                state[fault.column] ^= (fault.mask as u32) << (8 * fault.row);
+            }
+            mix_columns(&mut state);
+            add_round_key(&mut state, w[4 * round..=4 * round + 3].try_into().unwrap());
+        }
+
+        sub_bytes(&mut state);
+        shift_rows(&mut state);
+        add_round_key(&mut state, w[4 * N_R..=4 * N_R + 3].try_into().unwrap());
+
+        block_from_state(state)
+    }
+}
+
+// MixColumns sends a difference d in row r of a column to these multiples of
+// d down the column (column r of the MixColumns matrix)
+const MIX_COLUMN_COEFFICIENTS: [[u8; 4]; 4] =
+    [[2, 1, 1, 3], [3, 2, 1, 1], [1, 3, 2, 1], [1, 1, 3, 2]];
+
+// Undo the final ShiftRows so the four faulty bytes line up in one column.
+// Because ShiftRows commutes with AddRoundKey this works on the round key too.
+fn unshifted(block: [u8; 16]) -> [[u8; 4]; 4] {
+    let mut state: State = state_from_block(block);
+    inv_shift_rows(&mut state);
+    state.map(|x| x.to_le_bytes())
+}
+
+// the column the fault landed in, if the pair looks like a single byte fault
+pub fn faulty_column(correct: [u8; 16], faulty: [u8; 16]) -> Option<usize> {
+    let (a, b) = (unshifted(correct), unshifted(faulty));
+    let columns: Vec<usize> = (0..4).filter(|c| a[*c] != b[*c]).collect();
+    match columns.as_slice() {
+        [c] if (0..4).all(|r| a[*c][r] != b[*c][r]) => Some(*c),
+        _ => None,
+    }
+}
+
+// All four-byte last round key columns consistent with one faulty pair
+fn column_candidates(correct: [u8; 4], faulty: [u8; 4]) -> HashSet<[u8; 4]> {
+    // for each row, which key bytes give each input difference to SubBytes
+    let by_difference: Vec<Vec<Vec<u8>>> = (0..4)
+        .map(|row| {
+            let mut table = vec![vec![]; 256];
+            for k in 0..=0xff {
+                let d =
+                    INV_SBOX[(correct[row] ^ k) as usize] ^ INV_SBOX[(faulty[row] ^ k) as usize];
+                table[d as usize].push(k);
+            }
+            table
+        })
+        .collect();
+
+    let mut candidates = HashSet::new();
+    for coefficients in MIX_COLUMN_COEFFICIENTS {
+        for delta in 1..=0xff {
+            let options: Vec<&Vec<u8>> = (0..4)
+                .map(|row| &by_difference[row][xtimes(coefficients[row], delta) as usize])
+                .collect();
+            if options.iter().any(|x| x.is_empty()) {
+                continue;
+            }
+            for k0 in options[0] {
+                for k1 in options[1] {
+                    for k2 in options[2] {
+                        for k3 in options[3] {
+                            candidates.insert([*k0, *k1, *k2, *k3]);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    candidates
+}
+
+// Last round key from (correct, faulty) cyphertext pairs of the same
+// plaintext. Each column needs faults of its own, usually two apiece.
+pub fn recover_last_round_key(pairs: &[([u8; 16], [u8; 16])]) -> Option<[u8; 16]> {
+    let mut columns: [Option<HashSet<[u8; 4]>>; 4] = Default::default();
+    for (correct, faulty) in pairs {
+        let Some(c) = faulty_column(*correct, *faulty) else {
+            continue;
+        };
+        let candidates = column_candidates(unshifted(*correct)[c], unshifted(*faulty)[c]);
+        columns[c] = Some(match columns[c].take() {
+            None => candidates,
+            Some(previous) => previous.intersection(&candidates).copied().collect(),
+        });
+    }
+
+    let mut round_key: State = [0u32; 4];
+    for (c, candidates) in columns.iter().enumerate() {
+        let candidates = candidates.as_ref()?;
+        if candidates.len() != 1 {
+            return None;
+        }
+        round_key[c] = u32::from_le_bytes(*candidates.iter().next().unwrap());
+    }
+    shift_rows(&mut round_key);
+    Some(block_from_state(round_key))
+}
+
+pub fn recover_key(pairs: &[([u8; 16], [u8; 16])]) -> Option<Vec<u8>> {
+    let round_key = recover_last_round_key(pairs)?;
+    Some(key_from_round_key(&round_key, N_R, N_K))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes::encrypt_aes_128_ecb;
+    use crate::bytes::ParseBytes;
+    use assert_hex::assert_eq_hex;
+
+    #[test]
+    fn test_faulty_aes_without_fault() {
+        let key = Vec::from_hex_byte_array(b"000102030405060708090a0b0c0d0e0f").unwrap();
+        let block = *b"YELLOW SUBMARINE";
+        assert_eq_hex!(
+            FaultyAes::new(&key).encrypt(block, None).to_vec(),
+            encrypt_aes_128_ecb(&key, &block)
+        );
+    }
+
+    #[test]
+    fn test_dfa_recovers_key() {
+        let key = Vec::from_hex_byte_array(b"2b7e151628aed2a6abf7158809cf4f3c").unwrap();
+        let aes = FaultyAes::new(&key);
+        let block = *b"Attack at dawn!!";
+        let correct = aes.encrypt(block, None);
+        let faults = [
+            (0, 0, 0x1e),
+            (0, 2, 0xa7),
+            (1, 1, 0x05),
+            (1, 3, 0x80),
+            (2, 0, 0x3c),
+            (2, 3, 0xff),
+            (3, 2, 0x42),
+            (3, 1, 0x99),
+        ];
+        let pairs: Vec<([u8; 16], [u8; 16])> = faults
+            .iter()
+            .map(|(column, row, mask)| {
+                let fault = Fault {
+                    column: *column,
+                    row: *row,
+                    mask: *mask,
+                };
+                let faulty = aes.encrypt(block, Some(fault));
+                assert_eq!(faulty_column(correct, faulty), Some(*column));
+                (correct, faulty)
+            })
+            .collect();
+
+        // one pair per column isn't enough
+        let one_per_column = [pairs[0], pairs[2], pairs[4], pairs[6]];
+        assert_eq!(recover_last_round_key(&one_per_column), None);
+        assert_eq_hex!(recover_key(&pairs).unwrap(), key);
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::HashSet;
+
+use rand::Rng;
+
+use crate::aes::{encrypt_aes_128_cbc, encrypt_aes_128_ecb};
+use crate::bytes::pkcs7_pad;
+use crate::oracle::Oracle;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    Ecb,
+    Cbc,
+}
+
+// blocks that repeat an earlier block
+pub fn count_duplicate_blocks(cyphertext: &[u8], block_size: usize) -> usize {
+    let mut seen = HashSet::new();
+    cyphertext
+        .chunks_exact(block_size)
+        .filter(|block| !seen.insert(*block))
+        .count()
+}
+
+// Fraction of blocks that repeat an earlier one. Under a decent cypher in a
+// chained mode that's 0 for all practical purposes, so anything above 0 is
+// ECB encrypting repeated plaintext.
+pub fn detect_ecb(cyphertext: &[u8], block_size: usize) -> f32 {
+    let blocks = cyphertext.len() / block_size;
+    if blocks < 2 {
+        return 0.0;
+    }
+    count_duplicate_blocks(cyphertext, block_size) as f32 / (blocks - 1) as f32
+}
+
+// Exercise 11: a fresh random key, 5-10 random bytes either side of the
+// input, and a coin toss between ECB and CBC with a random IV. The mode is
+// returned so callers can check a guess.
+pub fn encryption_oracle(input: &[u8], rng: &mut impl Rng) -> (Mode, Vec<u8>) {
+    let key: [u8; 16] = rng.gen();
+    let mut plaintext: Vec<u8> = (0..rng.gen_range(5..=10)).map(|_| rng.gen()).collect();
+    plaintext.extend_from_slice(input);
+    plaintext.extend((0..rng.gen_range(5..=10)).map(|_| rng.gen::<u8>()));
+    let plaintext = pkcs7_pad(&plaintext, 16);
+    if rng.gen() {
+        (Mode::Ecb, encrypt_aes_128_ecb(&key, &plaintext))
+    } else {
+        let iv: [u8; 16] = rng.gen();
+        (Mode::Cbc, encrypt_aes_128_cbc(&key, &iv, &plaintext))
+    }
+}
+
+// Whatever the oracle puts in front, enough identical input bytes leave at
+// least two whole identical plaintext blocks, which ECB gives away.
+pub fn detect_mode(
+    oracle: &mut impl Oracle<Input = [u8], Output = Vec<u8>>,
+    block_size: usize,
+) -> Mode {
+    let cyphertext = oracle.query(&vec![b'A'; 3 * block_size]);
+    if count_duplicate_blocks(&cyphertext, block_size) > 0 {
+        Mode::Ecb
+    } else {
+        Mode::Cbc
+    }
+}
+
+// Exercises 12 and 14: ECB under a fixed random key of an optional fixed
+// random prefix, the attacker's input, then a secret to be recovered.
+pub struct AppendingOracle {
+    key: [u8; 16],
+    prefix: Vec<u8>,
+    secret: Vec<u8>,
+}
+
+impl AppendingOracle {
+    pub fn new(secret: &[u8], rng: &mut impl Rng) -> Self {
+        AppendingOracle {
+            key: rng.gen(),
+            prefix: vec![],
+            secret: secret.to_vec(),
+        }
+    }
+
+    pub fn with_random_prefix(secret: &[u8], rng: &mut impl Rng) -> Self {
+        let prefix_length = rng.gen_range(0..64);
+        AppendingOracle {
+            key: rng.gen(),
+            prefix: (0..prefix_length).map(|_| rng.gen()).collect(),
+            secret: secret.to_vec(),
+        }
+    }
+}
+
+impl Oracle for AppendingOracle {
+    type Input = [u8];
+    type Output = Vec<u8>;
+    fn query(&mut self, input: &[u8]) -> Vec<u8> {
+        let mut plaintext = self.prefix.clone();
+        plaintext.extend_from_slice(input);
+        plaintext.extend_from_slice(&self.secret);
+        encrypt_aes_128_ecb(&self.key, &pkcs7_pad(&plaintext, 16))
+    }
+}
+
+// Block size, and how many input bytes it takes to make the cyphertext grow
+// by a block, which is also how far the unpadded plaintext is from a block
+// boundary with empty input.
+pub fn detect_block_size(
+    oracle: &mut impl Oracle<Input = [u8], Output = Vec<u8>>,
+) -> (usize, usize) {
+    let initial = oracle.query(&[]).len();
+    for fill in 1..=256 {
+        let length = oracle.query(&vec![b'A'; fill]).len();
+        if length > initial {
+            return (length - initial, fill);
+        }
+    }
+    panic!("no block size up to 256 bytes");
+}
+
+// The first block that changes with the first input byte holds the end of
+// the prefix. It stops changing once enough fixed bytes go in front.
+pub fn detect_prefix_length(
+    oracle: &mut impl Oracle<Input = [u8], Output = Vec<u8>>,
+    block_size: usize,
+) -> usize {
+    let differs = |a: &[u8], b: &[u8], block: usize| {
+        a[block * block_size..(block + 1) * block_size]
+            != b[block * block_size..(block + 1) * block_size]
+    };
+    let a = oracle.query(&[0]);
+    let b = oracle.query(&[1]);
+    let block = (0..a.len() / block_size)
+        .find(|block| differs(&a, &b, *block))
+        .expect("input doesn't change the cyphertext");
+    for fill in 1..=block_size {
+        let mut input = vec![0; fill + 1];
+        let a = oracle.query(&input);
+        input[fill] = 1;
+        let b = oracle.query(&input);
+        if !differs(&a, &b, block) {
+            return (block + 1) * block_size - fill;
+        }
+    }
+    unreachable!("a block's worth of fill always pushes the change along")
+}
+
+// Byte-at-a-time ECB decryption, exercises 12 and 14. Line the next unknown
+// secret byte up as the last byte of a block whose other bytes are known,
+// then find which of 256 guesses encrypts to the same block.
+pub fn byte_at_a_time(oracle: &mut impl Oracle<Input = [u8], Output = Vec<u8>>) -> Vec<u8> {
+    let (block_size, fill) = detect_block_size(oracle);
+    assert_eq!(detect_mode(oracle, block_size), Mode::Ecb);
+    let prefix_length = detect_prefix_length(oracle, block_size);
+    let secret_length = oracle.query(&[]).len() - prefix_length - fill;
+
+    // bytes to bring the prefix to a block boundary, and the first block
+    // after it that we control
+    let align = (block_size - prefix_length % block_size) % block_size;
+    let first_block = (prefix_length + align) / block_size;
+
+    let mut known = vec![b'A'; block_size - 1];
+    for i in 0..secret_length {
+        let shift = block_size - 1 - i % block_size;
+        let target_block = first_block + i / block_size;
+        let cyphertext = oracle.query(&vec![b'A'; align + shift]);
+        let target = &cyphertext[target_block * block_size..(target_block + 1) * block_size];
+
+        let mut input = vec![b'A'; align];
+        input.extend_from_slice(&known[known.len() - (block_size - 1)..]);
+        input.push(0);
+        let found = (0..=0xff).find(|guess| {
+            input[align + block_size - 1] = *guess;
+            let cyphertext = oracle.query(&input);
+            &cyphertext[first_block * block_size..(first_block + 1) * block_size] == target
+        });
+        known.push(found.expect("no guess matched"));
+    }
+    known.split_off(block_size - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bytes::ParseBytes;
+    use crate::oracle::{from_fn, Counting};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    const SECRET_12: &[u8] = b"Um9sbGluJyBpbiBteSA1LjAKV2l0aCBteSByYWctdG9wIGRvd24gc28gbXkg\
+        aGFpciBjYW4gYmxvdwpUaGUgZ2lybGllcyBvbiBzdGFuZGJ5IHdhdmluZyBq\
+        dXN0IHRvIHNheSBoaQpEaWQgeW91IHN0b3A/IE5vLCBJIGp1c3QgZHJvdmUg\
+        YnkK";
+
+    #[test]
+    fn test_detect_ecb() {
+        let key = b"YELLOW SUBMARINE";
+        let plaintext = b"sixteen byte blksixteen byte blkanother block!!!";
+        let cyphertext = encrypt_aes_128_ecb(key, plaintext);
+        assert_eq!(count_duplicate_blocks(&cyphertext, 16), 1);
+        assert_eq!(detect_ecb(&cyphertext, 16), 0.5);
+        let cyphertext = encrypt_aes_128_cbc(key, &[0; 16], plaintext);
+        assert_eq!(detect_ecb(&cyphertext, 16), 0.0);
+        assert_eq!(detect_ecb(b"abab", 1), 2.0 / 3.0);
+    }
+
+    // Exercise 11 solution
+    #[test]
+    fn test_detect_mode() {
+        let mut rng = StdRng::seed_from_u64(11);
+        for _ in 0..50 {
+            let mut actual = None;
+            let mut oracle = Counting::new(from_fn(|input: &[u8]| {
+                let (mode, cyphertext) = encryption_oracle(input, &mut rng);
+                actual = Some(mode);
+                cyphertext
+            }));
+            let guess = detect_mode(&mut oracle, 16);
+            assert_eq!(oracle.queries(), 1);
+            assert_eq!(Some(guess), actual);
+        }
+    }
+
+    // Exercise 12 solution
+    #[test]
+    fn test_byte_at_a_time() {
+        let mut rng = StdRng::seed_from_u64(12);
+        let secret = Vec::from_base64_byte_array(SECRET_12).unwrap();
+        let mut oracle = Counting::new(AppendingOracle::new(&secret, &mut rng));
+        assert_eq!(detect_block_size(&mut oracle).0, 16);
+        assert_eq!(detect_prefix_length(&mut oracle, 16), 0);
+        assert_eq!(byte_at_a_time(&mut oracle), secret);
+        // at most one dictionary of 256 guesses per secret byte
+        assert!(oracle.queries() <= 256 * secret.len());
+    }
+
+    // Exercise 14 solution
+    #[test]
+    fn test_byte_at_a_time_with_prefix() {
+        let mut rng = StdRng::seed_from_u64(14);
+        let secret = Vec::from_base64_byte_array(SECRET_12).unwrap();
+        for _ in 0..5 {
+            let mut oracle = AppendingOracle::with_random_prefix(&secret, &mut rng);
+            let prefix_length = oracle.prefix.len();
+            assert_eq!(detect_prefix_length(&mut oracle, 16), prefix_length);
+            assert_eq!(byte_at_a_time(&mut oracle), secret);
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use crate::bytes::{transpose_by_block, xor_byte_array};
+use crate::frequency::{break_single_byte_xor, distance_metric};
+
+// Exercises 19 and 20: many messages under CTR with the same key and nonce
+// share one keystream, so it's repeating-key xor with as many repeats as
+// there are messages.
+
+// Truncated to the shortest message, every message contributes a byte to
+// every column, so each column is plain single byte xor.
+pub fn break_common_keystream(cyphertexts: &[Vec<u8>]) -> Vec<u8> {
+    let length = cyphertexts
+        .iter()
+        .map(|x| x.len())
+        .min()
+        .expect("no cyphertexts");
+    let truncated: Vec<u8> = cyphertexts
+        .iter()
+        .flat_map(|x| &x[..length])
+        .copied()
+        .collect();
+    transpose_by_block(length, &truncated)
+        .into_iter()
+        .map(|column| break_single_byte_xor(column).1)
+        .collect()
+}
+
+// messages often end in punctuation, which shouldn't count against a guess
+// in the last column of that message
+const FINAL_PUNCTUATION: &[u8] = b".,;:!?'\"";
+
+// Past the shortest message the columns shrink, and a column alone is too
+// little text to score. Instead score each guess by how the messages long
+// enough to reach it read up to and including that position.
+fn score_column(cyphertexts: &[&Vec<u8>], keystream: &[u8], position: usize, guess: u8) -> f32 {
+    let mut decrypted = vec![];
+    let mut forgiven = 0;
+    for cyphertext in cyphertexts {
+        let mut prefix = xor_byte_array(&cyphertext[..position], &keystream[..position]);
+        let last = cyphertext[position] ^ guess;
+        if position == cyphertext.len() - 1 && FINAL_PUNCTUATION.contains(&last) {
+            forgiven += 1;
+        }
+        prefix.push(last);
+        decrypted.append(&mut prefix);
+    }
+    distance_metric(&decrypted) - forgiven as f32
+}
+
+pub fn extend_keystream(cyphertexts: &[Vec<u8>], keystream: &[u8]) -> Vec<u8> {
+    let longest = cyphertexts.iter().map(|x| x.len()).max().unwrap_or(0);
+    let mut keystream = keystream.to_vec();
+    for position in keystream.len()..longest {
+        let reaching: Vec<&Vec<u8>> = cyphertexts.iter().filter(|x| x.len() > position).collect();
+        let best = (0x00..=0xff)
+            .map(|guess| (score_column(&reaching, &keystream, position, guess), guess))
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .unwrap()
+            .1;
+        keystream.push(best);
+    }
+    keystream
+}
+
+// keystream as long as the longest message, the tail less reliable the
+// fewer messages reach it
+pub fn break_fixed_nonce(cyphertexts: &[Vec<u8>]) -> Vec<u8> {
+    extend_keystream(cyphertexts, &break_common_keystream(cyphertexts))
+}
+
+pub fn decrypt_all(cyphertexts: &[Vec<u8>], keystream: &[u8]) -> Vec<Vec<u8>> {
+    cyphertexts
+        .iter()
+        .map(|x| xor_byte_array(x, &keystream[..x.len()]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bytes::ParseBytes;
+    use crate::ctr::aes_128_ctr;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn encrypted_poem() -> (Vec<Vec<u8>>, Vec<Vec<u8>>) {
+        let mut rng = StdRng::seed_from_u64(19);
+        let key: [u8; 16] = rng.gen();
+        let plaintexts: Vec<Vec<u8>> = include_str!("../data/19.txt")
+            .lines()
+            .map(|x| Vec::from_base64_byte_array(x.as_bytes()).unwrap())
+            .collect();
+        let cyphertexts = plaintexts.iter().map(|x| aes_128_ctr(&key, 0, x)).collect();
+        (plaintexts, cyphertexts)
+    }
+
+    // exercise 20's method, truncating to the shortest message, run over the
+    // exercise 19 data since 20.txt isn't in data/
+    #[test]
+    fn test_break_common_keystream() {
+        let (plaintexts, cyphertexts) = encrypted_poem();
+        let keystream = break_common_keystream(&cyphertexts);
+        assert_eq!(keystream.len(), 20);
+        // the frequency model ignores case, and the first column is all
+        // letters, so it can't tell capitals from the same text flipped by 0x20
+        for (plaintext, cyphertext) in plaintexts.iter().zip(&cyphertexts) {
+            let decrypted = xor_byte_array(&cyphertext[..20], &keystream);
+            assert!(decrypted.eq_ignore_ascii_case(&plaintext[..20]));
+            assert_eq!(decrypted[1..], plaintext[1..20]);
+        }
+    }
+
+    // Exercise 19 solution
+    #[test]
+    fn test_break_fixed_nonce() {
+        let (plaintexts, cyphertexts) = encrypted_poem();
+        let decrypted = decrypt_all(&cyphertexts, &break_fixed_nonce(&cyphertexts));
+        let total: usize = plaintexts.iter().map(|x| x.len()).sum();
+        let correct: usize = plaintexts
+            .iter()
+            .zip(&decrypted)
+            .map(|(a, b)| a.iter().zip(b).filter(|(x, y)| x == y).count())
+            .sum();
+        assert!(correct * 100 >= total * 90);
+    }
+}
@@ -0,0 +1,513 @@
+use std::ops::{BitXor, BitXorAssign};
+
+// Linear algebra over GF(2), bits packed 64 to a word, low bit first.
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BitVec {
+    len: usize,
+    words: Vec<u64>,
+}
+
+impl BitVec {
+    pub fn zeros(len: usize) -> Self {
+        BitVec {
+            len,
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    pub fn unit(len: usize, i: usize) -> Self {
+        let mut x = BitVec::zeros(len);
+        x.set(i, true);
+        x
+    }
+
+    pub fn from_bits(bits: &[bool]) -> Self {
+        let mut x = BitVec::zeros(bits.len());
+        for (i, bit) in bits.iter().enumerate() {
+            x.set(i, *bit);
+        }
+        x
+    }
+
+    // bits past len must be clear
+    pub fn from_words(len: usize, words: Vec<u64>) -> Self {
+        assert_eq!(words.len(), len.div_ceil(64));
+        assert!(len.is_multiple_of(64) || words.last().unwrap() >> (len % 64) == 0);
+        BitVec { len, words }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.len);
+        self.words[i / 64] >> (i % 64) & 1 == 1
+    }
+
+    pub fn set(&mut self, i: usize, bit: bool) {
+        assert!(i < self.len);
+        let mask = 1 << (i % 64);
+        match bit {
+            true => self.words[i / 64] |= mask,
+            false => self.words[i / 64] &= !mask,
+        }
+    }
+
+    pub fn flip(&mut self, i: usize) {
+        assert!(i < self.len);
+        self.words[i / 64] ^= 1 << (i % 64);
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.words.iter().all(|x| *x == 0)
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|x| x.count_ones() as usize).sum()
+    }
+
+    pub fn lowest_set(&self) -> Option<usize> {
+        self.lowest_set_from(0)
+    }
+
+    // the lowest set bit at or after i, for when the bits before are known
+    // to be clear
+    fn lowest_set_from(&self, i: usize) -> Option<usize> {
+        let start = i / 64;
+        let mut first = self.words.get(start)? & (!0 << (i % 64));
+        for word in start..self.words.len() {
+            if word > start {
+                first = self.words[word];
+            }
+            if first != 0 {
+                return Some(word * 64 + first.trailing_zeros() as usize);
+            }
+        }
+        None
+    }
+
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, word)| {
+            let mut word = *word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(i * 64 + bit)
+            })
+        })
+    }
+
+    // inner product
+    pub fn dot(&self, other: &BitVec) -> bool {
+        assert_eq!(self.len, other.len);
+        let ones: u32 = self
+            .words
+            .iter()
+            .zip(&other.words)
+            .map(|(x, y)| (x & y).count_ones())
+            .sum();
+        ones % 2 == 1
+    }
+
+    // xor in other's words in a range, when the rest of other is zero
+    fn xor_words(&mut self, other: &BitVec, words: std::ops::Range<usize>) {
+        assert_eq!(self.len, other.len);
+        for (x, y) in self.words[words.clone()]
+            .iter_mut()
+            .zip(&other.words[words])
+        {
+            *x ^= y;
+        }
+    }
+
+    // one past the last nonzero word
+    fn words_used(&self) -> usize {
+        self.words
+            .iter()
+            .rposition(|x| *x != 0)
+            .map_or(0, |i| i + 1)
+    }
+}
+
+impl BitXorAssign<&BitVec> for BitVec {
+    fn bitxor_assign(&mut self, other: &BitVec) {
+        self.xor_words(other, 0..other.words.len());
+    }
+}
+
+impl BitXor for &BitVec {
+    type Output = BitVec;
+    fn bitxor(self, other: &BitVec) -> BitVec {
+        let mut x = self.clone();
+        x ^= other;
+        x
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitMatrix {
+    columns: usize,
+    rows: Vec<BitVec>,
+}
+
+impl BitMatrix {
+    pub fn new(columns: usize) -> Self {
+        BitMatrix {
+            columns,
+            rows: vec![],
+        }
+    }
+
+    pub fn zeros(rows: usize, columns: usize) -> Self {
+        BitMatrix {
+            columns,
+            rows: vec![BitVec::zeros(columns); rows],
+        }
+    }
+
+    pub fn identity(n: usize) -> Self {
+        BitMatrix {
+            columns: n,
+            rows: (0..n).map(|i| BitVec::unit(n, i)).collect(),
+        }
+    }
+
+    pub fn from_rows(columns: usize, rows: Vec<BitVec>) -> Self {
+        assert!(rows.iter().all(|x| x.len() == columns));
+        BitMatrix { columns, rows }
+    }
+
+    pub fn push_row(&mut self, row: BitVec) {
+        assert_eq!(row.len(), self.columns);
+        self.rows.push(row);
+    }
+
+    pub fn rows(&self) -> &[BitVec] {
+        &self.rows
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn num_columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> bool {
+        self.rows[row].get(column)
+    }
+
+    pub fn set(&mut self, row: usize, column: usize, bit: bool) {
+        self.rows[row].set(column, bit)
+    }
+
+    pub fn mul_vec(&self, x: &BitVec) -> BitVec {
+        BitVec::from_bits(
+            &self
+                .rows
+                .iter()
+                .map(|row| row.dot(x))
+                .collect::<Vec<bool>>(),
+        )
+    }
+
+    pub fn mul(&self, other: &BitMatrix) -> BitMatrix {
+        assert_eq!(self.columns, other.num_rows());
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                row.ones().fold(BitVec::zeros(other.columns), |mut sum, i| {
+                    sum ^= &other.rows[i];
+                    sum
+                })
+            })
+            .collect();
+        BitMatrix {
+            columns: other.columns,
+            rows,
+        }
+    }
+
+    pub fn transpose(&self) -> BitMatrix {
+        let mut transposed = BitMatrix::zeros(self.columns, self.rows.len());
+        for (i, row) in self.rows.iter().enumerate() {
+            for j in row.ones() {
+                transposed.rows[j].set(i, true);
+            }
+        }
+        transposed
+    }
+
+    // Gauss-Jordan in place, to reduced row echelon form with the zero rows
+    // dropped; returns the pivot column of each row
+    pub fn reduce(&mut self) -> Vec<usize> {
+        let mut pivots = vec![];
+        let mut next = 0;
+        for column in 0..self.columns {
+            let Some(found) = (next..self.rows.len()).find(|i| self.rows[*i].get(column)) else {
+                continue;
+            };
+            self.rows.swap(next, found);
+            let (before, after) = self.rows.split_at_mut(next);
+            let (pivot_row, after) = after.split_first_mut().unwrap();
+            for row in before.iter_mut().chain(after.iter_mut()) {
+                if row.get(column) {
+                    row.xor_words(pivot_row, column / 64..pivot_row.words.len());
+                }
+            }
+            pivots.push(column);
+            next += 1;
+        }
+        self.rows.truncate(next);
+        pivots
+    }
+
+    pub fn rank(&self) -> usize {
+        let mut system = Echelon::new(self.columns);
+        for row in &self.rows {
+            let _ = system.insert(row.clone(), false);
+        }
+        system.rank()
+    }
+
+    // a basis for the x with M x = 0
+    pub fn kernel(&self) -> Vec<BitVec> {
+        let mut reduced = self.clone();
+        let pivots = reduced.reduce();
+        (0..self.columns)
+            .filter(|column| !pivots.contains(column))
+            .map(|free| {
+                let mut x = BitVec::unit(self.columns, free);
+                for (row, pivot) in reduced.rows.iter().zip(&pivots) {
+                    if row.get(free) {
+                        x.set(*pivot, true);
+                    }
+                }
+                x
+            })
+            .collect()
+    }
+
+    // one x with M x = b, free unknowns zero; add kernel vectors for the rest
+    pub fn solve(&self, b: &BitVec) -> Option<BitVec> {
+        assert_eq!(b.len(), self.rows.len());
+        let mut system = Echelon::new(self.columns);
+        for (i, row) in self.rows.iter().enumerate() {
+            system.insert(row.clone(), b.get(i)).ok()?;
+        }
+        Some(system.solve())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Inconsistent;
+
+// Equations eliminated as they arrive, so redundant ones cost nothing to
+// keep. For large systems that mostly touch a few unknowns each this is far
+// quicker than reducing a whole matrix.
+pub struct Echelon {
+    unknowns: usize,
+    // each row's lowest set coefficient is its pivot, and it keeps how many
+    // words its coefficients reach so sparse rows are quick to xor in
+    rows: Vec<(BitVec, bool, usize)>,
+    pivots: Vec<Option<usize>>,
+}
+
+impl Echelon {
+    pub fn new(unknowns: usize) -> Self {
+        Echelon {
+            unknowns,
+            rows: vec![],
+            pivots: vec![None; unknowns],
+        }
+    }
+
+    pub fn rank(&self) -> usize {
+        self.rows.len()
+    }
+
+    // Rows only reach unknowns past their pivot, so if every unknown from
+    // some point on has a pivot, all of those are pinned down.
+    pub fn has_pivot(&self, unknown: usize) -> bool {
+        self.pivots[unknown].is_some()
+    }
+
+    // Ok(true) if the equation told us something new, Ok(false) if it
+    // followed from the ones before
+    pub fn insert(
+        &mut self,
+        mut coefficients: BitVec,
+        mut constant: bool,
+    ) -> Result<bool, Inconsistent> {
+        assert_eq!(coefficients.len(), self.unknowns);
+        let mut from = 0;
+        while let Some(pivot) = coefficients.lowest_set_from(from) {
+            match self.pivots[pivot] {
+                Some(row) => {
+                    let (reducer, reducer_constant, used) = &self.rows[row];
+                    // everything below the pivot is already clear
+                    coefficients.xor_words(reducer, pivot / 64..*used);
+                    constant ^= reducer_constant;
+                    from = pivot + 1;
+                }
+                None => {
+                    self.pivots[pivot] = Some(self.rows.len());
+                    let used = coefficients.words_used();
+                    self.rows.push((coefficients, constant, used));
+                    return Ok(true);
+                }
+            }
+        }
+        match constant {
+            true => Err(Inconsistent),
+            false => Ok(false),
+        }
+    }
+
+    // one solution, with unknowns nothing pinned down set to zero
+    pub fn solve(&self) -> BitVec {
+        let mut solution = BitVec::zeros(self.unknowns);
+        for pivot in (0..self.unknowns).rev() {
+            if let Some(row) = self.pivots[pivot] {
+                let (coefficients, constant, _) = &self.rows[row];
+                if coefficients.dot(&solution) != *constant {
+                    solution.flip(pivot);
+                }
+            }
+        }
+        solution
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+    fn bits(s: &str) -> BitVec {
+        BitVec::from_bits(&s.bytes().map(|x| x == b'1').collect::<Vec<bool>>())
+    }
+
+    fn random_matrix(rows: usize, columns: usize, rng: &mut impl Rng) -> BitMatrix {
+        let rows = (0..rows)
+            .map(|_| BitVec::from_bits(&(0..columns).map(|_| rng.gen()).collect::<Vec<bool>>()))
+            .collect();
+        BitMatrix::from_rows(columns, rows)
+    }
+
+    #[test]
+    fn test_bitvec() {
+        let mut x = BitVec::zeros(130);
+        x.set(3, true);
+        x.set(64, true);
+        x.set(129, true);
+        assert_eq!(x.ones().collect::<Vec<usize>>(), vec![3, 64, 129]);
+        assert_eq!(x.count_ones(), 3);
+        assert_eq!(x.lowest_set(), Some(3));
+        assert_eq!(x.lowest_set_from(65), Some(129));
+        let y = &x ^ &BitVec::unit(130, 64);
+        assert_eq!(y.ones().collect::<Vec<usize>>(), vec![3, 129]);
+        assert!(x.dot(&BitVec::unit(130, 129)));
+        assert!(!x.dot(&y));
+        assert!((&x ^ &x).is_zero());
+        assert_eq!(bits("1101").words(), &[0b1011]);
+    }
+
+    #[test]
+    fn test_echelon() {
+        // x0 ^ x1 = 1, x1 ^ x2 = 0, x0 ^ x2 = 1 (redundant), x2 = 1
+        let mut system = Echelon::new(3);
+        assert_eq!(system.insert(bits("110"), true), Ok(true));
+        assert_eq!(system.insert(bits("011"), false), Ok(true));
+        assert_eq!(system.insert(bits("101"), true), Ok(false));
+        assert_eq!(system.rank(), 2);
+        assert!(system.has_pivot(1) && !system.has_pivot(2));
+        assert_eq!(system.solve(), bits("100"));
+        assert_eq!(system.insert(bits("001"), true), Ok(true));
+        assert_eq!(system.solve(), bits("011"));
+        assert_eq!(system.insert(bits("100"), true), Err(Inconsistent));
+    }
+
+    #[test]
+    fn test_matrix() {
+        let m = BitMatrix::from_rows(4, vec![bits("1100"), bits("0110"), bits("1010")]);
+        assert_eq!(m.rank(), 2);
+        assert_eq!(m.transpose().transpose(), m);
+        assert_eq!(m.mul(&BitMatrix::identity(4)), m);
+        assert_eq!(m.mul_vec(&bits("1111")), bits("000"));
+
+        let kernel = m.kernel();
+        assert_eq!(kernel.len(), 2);
+        for x in &kernel {
+            assert!(m.mul_vec(x).is_zero());
+        }
+
+        let x = m.solve(&bits("101")).unwrap();
+        assert_eq!(m.mul_vec(&x), bits("101"));
+        assert_eq!(m.solve(&bits("100")), None);
+
+        let mut reduced = m.clone();
+        assert_eq!(reduced.reduce(), vec![0, 1]);
+        assert_eq!(reduced.rows(), &[bits("1010"), bits("0110")]);
+    }
+
+    #[test]
+    fn test_random_systems() {
+        let mut rng = StdRng::seed_from_u64(48);
+        let m = random_matrix(300, 200, &mut rng);
+        assert_eq!(m.rank(), 200);
+        let x = BitVec::from_bits(&(0..200).map(|_| rng.gen()).collect::<Vec<bool>>());
+        assert_eq!(m.solve(&m.mul_vec(&x)), Some(x));
+
+        // rank deficient: the product of 150 wide matrices
+        let m = random_matrix(250, 150, &mut rng).mul(&random_matrix(150, 250, &mut rng));
+        assert_eq!(m.rank(), 150);
+        let kernel = m.kernel();
+        assert_eq!(kernel.len(), 100);
+        assert!(kernel.iter().all(|x| m.mul_vec(x).is_zero()));
+        assert_eq!(BitMatrix::from_rows(250, kernel).rank(), 100);
+    }
+
+    #[test]
+    fn test_large_sparse_system() {
+        // 20000 unknowns, each equation touching a handful of nearby ones,
+        // as state recovery systems do, arriving in no particular order
+        let mut rng = StdRng::seed_from_u64(20000);
+        let n = 20000;
+        let x = BitVec::from_bits(&(0..n).map(|_| rng.gen()).collect::<Vec<bool>>());
+        let mut rows: Vec<BitVec> = (0..n)
+            .map(|i| {
+                let mut row = BitVec::unit(n, i);
+                for _ in 0..4 {
+                    row.flip(rng.gen_range(i.saturating_sub(256)..(i + 256).min(n)));
+                }
+                row
+            })
+            .collect();
+        rows.shuffle(&mut rng);
+        let mut system = Echelon::new(n);
+        for row in &rows {
+            system.insert(row.clone(), row.dot(&x)).unwrap();
+        }
+        // a few unknowns are left free, so check it's a solution rather than
+        // the same one
+        assert!(system.rank() > n - 10);
+        let solution = system.solve();
+        assert!(rows.iter().all(|row| row.dot(&solution) == row.dot(&x)));
+    }
+}
@@ -0,0 +1,259 @@
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
+
+// Elements of GF(2^8) = GF(2)[x] / p(x), with the reduction polynomial p
+// given as its bits including the x^8 term. AES uses x^8 + x^4 + x^3 + x + 1,
+// Reed-Solomon codes usually x^8 + x^4 + x^3 + x^2 + 1.
+pub const AES_POLYNOMIAL: u16 = 0x11b;
+pub const REED_SOLOMON_POLYNOMIAL: u16 = 0x11d;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Gf256<const POLY: u16 = AES_POLYNOMIAL>(pub u8);
+
+// multiplicative inverse, along the lines of num_traits::Inv
+pub trait Inv {
+    type Output;
+    fn inv(self) -> Self::Output;
+}
+
+// shift and add multiplication, only used to build the tables
+const fn slow_mul(a: u8, b: u8, poly: u16) -> u8 {
+    let mut a_mut = a;
+    let mut b_mut = b;
+    let mut result = 0u8;
+    while b_mut != 0 {
+        if b_mut & 1 == 1 {
+            result ^= a_mut;
+        }
+        let carry = a_mut & 0x80 == 0x80;
+        a_mut <<= 1;
+        if carry {
+            a_mut ^= (poly & 0xff) as u8;
+        }
+        b_mut >>= 1;
+    }
+    result
+}
+
+const fn multiplicative_order(g: u8, poly: u16) -> usize {
+    let mut acc = g;
+    let mut order = 1;
+    while acc != 1 && order < 256 {
+        acc = slow_mul(acc, g, poly);
+        order += 1;
+    }
+    order
+}
+
+// exp is twice as long as it needs to be so log a + log b never wraps
+pub struct Tables {
+    pub generator: u8,
+    pub exp: [u8; 512],
+    pub log: [u8; 256],
+}
+
+const fn generate_tables(poly: u16) -> Tables {
+    assert!(poly & 0x100 != 0, "reduction polynomial must have degree 8");
+    // x only generates the multiplicative group for primitive polynomials,
+    // so look for the smallest element that does
+    let mut generator = 2;
+    while multiplicative_order(generator, poly) != 255 {
+        assert!(generator != 255, "reduction polynomial is not irreducible");
+        generator += 1;
+    }
+
+    let mut exp = [0u8; 512];
+    let mut log = [0u8; 256];
+    let mut acc = 1u8;
+    let mut i = 0;
+    while i < 255 {
+        exp[i] = acc;
+        exp[i + 255] = acc;
+        log[acc as usize] = i as u8;
+        acc = slow_mul(acc, generator, poly);
+        i += 1;
+    }
+    Tables {
+        generator,
+        exp,
+        log,
+    }
+}
+
+impl<const POLY: u16> Gf256<POLY> {
+    pub const TABLES: Tables = generate_tables(POLY);
+    pub const ZERO: Self = Gf256(0);
+    pub const ONE: Self = Gf256(1);
+
+    pub const fn generator() -> Self {
+        Gf256(Self::TABLES.generator)
+    }
+
+    pub const fn checked_inv(self) -> Option<Self> {
+        if self.0 == 0 {
+            return None;
+        }
+        let log = Self::TABLES.log[self.0 as usize] as usize;
+        Some(Gf256(Self::TABLES.exp[255 - log]))
+    }
+
+    pub fn pow(self, n: u32) -> Self {
+        if n == 0 {
+            return Self::ONE;
+        }
+        if self.0 == 0 {
+            return Self::ZERO;
+        }
+        let log = Self::TABLES.log[self.0 as usize] as u64;
+        Gf256(Self::TABLES.exp[((log * n as u64) % 255) as usize])
+    }
+}
+
+impl<const POLY: u16> From<u8> for Gf256<POLY> {
+    fn from(x: u8) -> Self {
+        Gf256(x)
+    }
+}
+
+impl<const POLY: u16> From<Gf256<POLY>> for u8 {
+    fn from(x: Gf256<POLY>) -> Self {
+        x.0
+    }
+}
+
+// addition and subtraction are both xor
+impl<const POLY: u16> Add for Gf256<POLY> {
+    type Output = Self;
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn add(self, rhs: Self) -> Self {
+        Gf256(self.0 ^ rhs.0)
+    }
+}
+
+impl<const POLY: u16> Sub for Gf256<POLY> {
+    type Output = Self;
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn sub(self, rhs: Self) -> Self {
+        Gf256(self.0 ^ rhs.0)
+    }
+}
+
+impl<const POLY: u16> Mul for Gf256<POLY> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        if self.0 == 0 || rhs.0 == 0 {
+            return Self::ZERO;
+        }
+        let log_a = Self::TABLES.log[self.0 as usize] as usize;
+        let log_b = Self::TABLES.log[rhs.0 as usize] as usize;
+        Gf256(Self::TABLES.exp[log_a + log_b])
+    }
+}
+
+impl<const POLY: u16> Inv for Gf256<POLY> {
+    type Output = Self;
+    fn inv(self) -> Self {
+        self.checked_inv().expect("zero has no inverse")
+    }
+}
+
+impl<const POLY: u16> Div for Gf256<POLY> {
+    type Output = Self;
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self {
+        self * rhs.inv()
+    }
+}
+
+impl<const POLY: u16> AddAssign for Gf256<POLY> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<const POLY: u16> SubAssign for Gf256<POLY> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<const POLY: u16> MulAssign for Gf256<POLY> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<const POLY: u16> DivAssign for Gf256<POLY> {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+impl<const POLY: u16> std::iter::Sum for Gf256<POLY> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |a, b| a + b)
+    }
+}
+
+impl<const POLY: u16> std::iter::Product for Gf256<POLY> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |a, b| a * b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Aes = Gf256<AES_POLYNOMIAL>;
+    type ReedSolomon = Gf256<REED_SOLOMON_POLYNOMIAL>;
+
+    #[test]
+    // examples from section 4 of FIPS 197
+    fn test_aes_field() {
+        assert_eq!(Aes::from(0x57) + Aes::from(0x83), Aes::from(0xd4));
+        assert_eq!(Aes::from(0x57) * Aes::from(0x83), Aes::from(0xc1));
+        assert_eq!(Aes::from(0x57) * Aes::from(0x13), Aes::from(0xfe));
+        assert_eq!(Aes::from(0x53).inv(), Aes::from(0xca));
+        // x isn't a generator for the AES polynomial, x + 1 is
+        assert_eq!(Aes::generator(), Aes::from(0x03));
+    }
+
+    #[test]
+    fn test_against_slow_mul() {
+        for a in 0..=0xff {
+            for b in 0..=0xff {
+                assert_eq!(
+                    (Aes::from(a) * Aes::from(b)).0,
+                    slow_mul(a, b, AES_POLYNOMIAL)
+                );
+                assert_eq!(
+                    (ReedSolomon::from(a) * ReedSolomon::from(b)).0,
+                    slow_mul(a, b, REED_SOLOMON_POLYNOMIAL)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_inverse_and_division() {
+        assert_eq!(ReedSolomon::generator(), ReedSolomon::from(0x02));
+        assert_eq!(Aes::ZERO.checked_inv(), None);
+        for a in 1..=0xff {
+            let a = ReedSolomon::from(a);
+            assert_eq!(a * a.inv(), ReedSolomon::ONE);
+            assert_eq!(a.pow(255), ReedSolomon::ONE);
+            assert_eq!(a.pow(254), a.inv());
+            for b in [0x01, 0x02, 0x80, 0xff] {
+                let b = ReedSolomon::from(b);
+                assert_eq!(a * b / b, a);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sum_and_product() {
+        let xs: Vec<Aes> = [0x01, 0x02, 0x03].map(Aes::from).to_vec();
+        assert_eq!(xs.iter().copied().sum::<Aes>(), Aes::ZERO);
+        assert_eq!(xs.iter().copied().product::<Aes>(), Aes::from(0x06));
+    }
+}
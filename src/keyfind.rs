@@ -0,0 +1,164 @@
+use crate::aes::{inv_key_expansion, key_expansion, schedule_temp};
+use crate::distance::hamming_distance;
+
+// Cold boot style search for expanded AES key schedules in a memory image,
+// after Halderman et al. "Lest We Remember". Schedules are assumed to be
+// stored word by word, each word big-endian, as key_expansion lays them out.
+
+// (n_k, n_r) for AES-128, AES-192 and AES-256
+pub const KEY_SIZES: [(usize, usize); 3] = [(4, 10), (6, 12), (8, 14)];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FoundKey {
+    pub offset: usize,
+    pub key: Vec<u8>,
+    pub bit_errors: usize,
+}
+
+fn read_words(dump: &[u8], offset: usize, count: usize) -> Option<Vec<u32>> {
+    let bytes = dump.get(offset..offset + 4 * count)?;
+    Some(
+        bytes
+            .chunks_exact(4)
+            .map(|x| u32::from_be_bytes(x.try_into().unwrap()))
+            .collect(),
+    )
+}
+
+// Bits in which the words disagree with the key schedule recurrence, giving
+// up as soon as the count passes limit. A flipped bit in the dump shows up in
+// at most three checks, and through subword in at most 1 + 1 + 8 bits.
+fn recurrence_errors(w: &[u32], n_k: usize, limit: usize) -> Option<usize> {
+    let mut errors = 0;
+    for i in n_k..w.len() {
+        errors += (w[i] ^ w[i - n_k] ^ schedule_temp(w[i - 1], i, n_k)).count_ones() as usize;
+        if errors > limit {
+            return None;
+        }
+    }
+    Some(errors)
+}
+
+// Any n_k consecutive words pin down the cypher key, so try every window and
+// keep the key whose expansion is closest to what's in the dump.
+fn recover_key(w: &[u32], n_k: usize, n_r: usize) -> (Vec<u8>, usize) {
+    let dumped: Vec<u8> = w.iter().flat_map(|x| x.to_be_bytes()).collect();
+    (0..=w.len() - n_k)
+        .map(|first_word| {
+            let key = inv_key_expansion(&w[first_word..], first_word, n_k);
+            let expanded: Vec<u8> = key_expansion(&key, n_k, n_r)
+                .iter()
+                .flat_map(|x| x.to_be_bytes())
+                .collect();
+            (key, hamming_distance(&expanded, &dumped))
+        })
+        .min_by_key(|x| x.1)
+        .unwrap()
+}
+
+pub fn find_key_at(
+    dump: &[u8],
+    offset: usize,
+    n_k: usize,
+    n_r: usize,
+    max_bit_errors: usize,
+) -> Option<FoundKey> {
+    let w = read_words(dump, offset, 4 * (n_r + 1))?;
+    // memory that's been zeroed or filled satisfies nothing useful
+    if w[..n_k].iter().all(|x| *x == w[0]) {
+        return None;
+    }
+    recurrence_errors(&w, n_k, 10 * max_bit_errors)?;
+    let (key, bit_errors) = recover_key(&w, n_k, n_r);
+    if bit_errors > max_bit_errors {
+        return None;
+    }
+    Some(FoundKey {
+        offset,
+        key,
+        bit_errors,
+    })
+}
+
+pub fn find_keys(dump: &[u8], max_bit_errors: usize) -> Vec<FoundKey> {
+    let mut found = vec![];
+    for offset in 0..dump.len() {
+        for (n_k, n_r) in KEY_SIZES {
+            if let Some(key) = find_key_at(dump, offset, n_k, n_r, max_bit_errors) {
+                found.push(key);
+            }
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bytes::ParseBytes;
+    use assert_hex::assert_eq_hex;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn test_find_keys_clean() {
+        let key = Vec::from_hex_byte_array(b"2b7e151628aed2a6abf7158809cf4f3c").unwrap();
+        let mut dump = vec![0x5au8; 1000];
+        let schedule: Vec<u8> = key_expansion(&key, 4, 10)
+            .iter()
+            .flat_map(|x| x.to_be_bytes())
+            .collect();
+        dump[333..333 + schedule.len()].copy_from_slice(&schedule);
+        assert_eq!(
+            find_keys(&dump, 0),
+            vec![FoundKey {
+                offset: 333,
+                key,
+                bit_errors: 0
+            }]
+        );
+    }
+
+    // 64KiB of zeroed and random memory holding the Appendix A schedules of
+    // FIPS 197, each with a handful of 1 bits decayed to 0
+    fn decayed_dump(keys: &[(usize, Vec<u8>)], rng: &mut impl Rng) -> Vec<u8> {
+        let mut dump = vec![0u8; 0x10000];
+        rng.fill(&mut dump[0x8000..]);
+        for (offset, key) in keys {
+            let (n_k, n_r) = KEY_SIZES[key.len() / 8 - 2];
+            let mut schedule: Vec<u8> = key_expansion(key, n_k, n_r)
+                .iter()
+                .flat_map(|x| x.to_be_bytes())
+                .collect();
+            let mut decayed = 0;
+            while decayed < 6 {
+                let bit = rng.gen_range(0..8 * schedule.len());
+                if schedule[bit / 8] >> (bit % 8) & 1 == 1 {
+                    schedule[bit / 8] ^= 1 << (bit % 8);
+                    decayed += 1;
+                }
+            }
+            dump[*offset..offset + schedule.len()].copy_from_slice(&schedule);
+        }
+        dump
+    }
+
+    #[test]
+    fn test_find_keys_decayed() {
+        let keys = [
+            Vec::from_hex_byte_array(b"2b7e151628aed2a6abf7158809cf4f3c").unwrap(),
+            Vec::from_hex_byte_array(b"8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b").unwrap(),
+            Vec::from_hex_byte_array(
+                b"603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4",
+            )
+            .unwrap(),
+        ];
+        let placed: Vec<(usize, Vec<u8>)> =
+            [0x1234, 0x5678, 0x9abc].into_iter().zip(keys).collect();
+        let mut rng = StdRng::seed_from_u64(27);
+        let dump = decayed_dump(&placed, &mut rng);
+        let found = find_keys(&dump, 12);
+        assert!(found.iter().all(|x| x.bit_errors == 6));
+        let keys: Vec<(usize, Vec<u8>)> = found.into_iter().map(|x| (x.offset, x.key)).collect();
+        assert_eq_hex!(keys, placed);
+    }
+}
@@ -0,0 +1,109 @@
+// k=v&k=v structured cookies, in order, with the metacharacters & = and %
+// percent-escaped so values can't smuggle in fields of their own.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KvParseError;
+
+const METACHARACTERS: [char; 3] = ['%', '&', '='];
+
+pub fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if METACHARACTERS.contains(&c) {
+            escaped.push_str(&format!("%{:02X}", c as u8));
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+pub fn unescape(s: &str) -> Result<String, KvParseError> {
+    let bytes = s.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = bytes.get(i + 1..i + 3).ok_or(KvParseError)?;
+            // from_str_radix would also take a sign, as in "%+1"
+            if !hex.iter().all(u8::is_ascii_hexdigit) {
+                return Err(KvParseError);
+            }
+            let hex = std::str::from_utf8(hex).map_err(|_| KvParseError)?;
+            unescaped.push(u8::from_str_radix(hex, 16).map_err(|_| KvParseError)?);
+            i += 3;
+        } else {
+            unescaped.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(unescaped).map_err(|_| KvParseError)
+}
+
+pub fn encode(pairs: &[(String, String)]) -> String {
+    pairs
+        .iter()
+        .map(|(k, v)| format!("{}={}", escape(k), escape(v)))
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+pub fn parse(s: &str) -> Result<Vec<(String, String)>, KvParseError> {
+    if s.is_empty() {
+        return Ok(vec![]);
+    }
+    s.split('&')
+        .map(|pair| {
+            let (k, v) = pair.split_once('=').ok_or(KvParseError)?;
+            if v.contains('=') {
+                return Err(KvParseError);
+            }
+            Ok((unescape(k)?, unescape(v)?))
+        })
+        .collect()
+}
+
+// first value for key, as a query string would usually be read
+pub fn get<'a>(pairs: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    pairs
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(raw: &[(&str, &str)]) -> Vec<(String, String)> {
+        raw.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("foo=bar&baz=qux&zap=zazzle").unwrap(),
+            pairs(&[("foo", "bar"), ("baz", "qux"), ("zap", "zazzle")])
+        );
+        assert_eq!(parse("").unwrap(), vec![]);
+        assert_eq!(parse("foo"), Err(KvParseError));
+        assert_eq!(parse("foo=bar=baz"), Err(KvParseError));
+        assert_eq!(parse("foo=%4"), Err(KvParseError));
+        assert_eq!(parse("foo=%zz"), Err(KvParseError));
+        assert_eq!(parse("foo=%+1"), Err(KvParseError));
+    }
+
+    #[test]
+    fn test_encode_escapes() {
+        let profile = pairs(&[("email", "foo@bar.com&role=admin"), ("role", "user")]);
+        let encoded = encode(&profile);
+        assert_eq!(encoded, "email=foo@bar.com%26role%3Dadmin&role=user");
+        let parsed = parse(&encoded).unwrap();
+        assert_eq!(parsed, profile);
+        assert_eq!(get(&parsed, "role"), Some("user"));
+        assert_eq!(get(&parsed, "uid"), None);
+        assert_eq!(unescape(&escape("100% & more")).unwrap(), "100% & more");
+    }
+}
@@ -0,0 +1,170 @@
+// Linear congruential generators, x' = a x + c mod m, and getting their
+// parameters back from what they output.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lcg {
+    pub state: u64,
+    pub a: u64,
+    pub c: u64,
+    pub m: u64,
+}
+
+impl Lcg {
+    pub fn new(seed: u64, a: u64, c: u64, m: u64) -> Self {
+        Lcg {
+            state: seed,
+            a,
+            c,
+            m,
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state =
+            ((self.a as u128 * self.state as u128 + self.c as u128) % self.m as u128) as u64;
+        self.state
+    }
+}
+
+impl Iterator for Lcg {
+    type Item = u64;
+    fn next(&mut self) -> Option<u64> {
+        Some(self.next_u64())
+    }
+}
+
+// java.util.Random: 48 bits of state, scrambled seed, nextInt the top 32
+pub const JAVA_MULTIPLIER: u64 = 0x5deece66d;
+pub const JAVA_INCREMENT: u64 = 11;
+pub const JAVA_MODULUS: u64 = 1 << 48;
+
+pub fn java_random(seed: u64) -> Lcg {
+    Lcg::new(
+        (seed ^ JAVA_MULTIPLIER) % JAVA_MODULUS,
+        JAVA_MULTIPLIER,
+        JAVA_INCREMENT,
+        JAVA_MODULUS,
+    )
+}
+
+pub fn java_next_int(lcg: &mut Lcg) -> i32 {
+    (lcg.next_u64() >> 16) as i32
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    match b {
+        0 => a.abs(),
+        _ => gcd(b, a % b),
+    }
+}
+
+pub fn mod_inverse(a: i128, m: i128) -> Option<i128> {
+    let (mut r0, mut r1) = (a.rem_euclid(m), m);
+    let (mut s0, mut s1) = (1i128, 0i128);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (s0, s1) = (s1, s0 - q * s1);
+    }
+    (r0 == 1).then(|| s0.rem_euclid(m))
+}
+
+// With t the differences between outputs, t[n+1] = a t[n] mod m, so
+// t[n+2] t[n] - t[n+1]^2 is a multiple of m, and a few of them have m as
+// their gcd. Outputs below 2^62 keep the products inside an i128.
+pub fn recover_modulus(outputs: &[u64]) -> Option<u64> {
+    assert!(outputs.iter().all(|x| *x < 1 << 62), "outputs too large");
+    let t: Vec<i128> = outputs
+        .windows(2)
+        .map(|x| x[1] as i128 - x[0] as i128)
+        .collect();
+    let m = t.windows(3).map(|x| x[2] * x[0] - x[1] * x[1]).fold(0, gcd);
+    let largest = *outputs.iter().max()? as i128;
+    (m > largest).then_some(m as u64)
+}
+
+// a (x1 - x0) = x2 - x1 mod m, from the first triple where x1 - x0 is
+// invertible
+pub fn recover_multiplier(outputs: &[u64], m: u64) -> Option<u64> {
+    let m = m as i128;
+    outputs.windows(3).find_map(|x| {
+        let inverse = mod_inverse(x[1] as i128 - x[0] as i128, m)?;
+        Some(((x[2] as i128 - x[1] as i128).rem_euclid(m) * inverse % m) as u64)
+    })
+}
+
+pub fn recover_increment(outputs: &[u64], m: u64, a: u64) -> u64 {
+    let (m, a) = (m as i128, a as i128);
+    (outputs[1] as i128 - a * outputs[0] as i128).rem_euclid(m) as u64
+}
+
+// Everything from outputs alone, continuing after the last. A handful of
+// outputs usually does; more make a wrong modulus less likely.
+pub fn recover_lcg(outputs: &[u64]) -> Option<Lcg> {
+    let m = recover_modulus(outputs)?;
+    let a = recover_multiplier(outputs, m)?;
+    let c = recover_increment(outputs, m, a);
+    let lcg = Lcg::new(*outputs.last()?, a, c, m);
+    let mut check = Lcg::new(outputs[0], a, c, m);
+    check
+        .by_ref()
+        .take(outputs.len() - 1)
+        .eq(outputs[1..].iter().copied())
+        .then_some(lcg)
+}
+
+// Outputs with the low shift bits of state dropped, as Java's are, and the
+// parameters known: try every value of the missing bits against the rest.
+pub fn recover_truncated(outputs: &[u64], shift: u32, a: u64, c: u64, m: u64) -> Option<Lcg> {
+    assert!(shift <= 32, "too many missing bits to search");
+    (0..1u64 << shift).find_map(|low| {
+        let state = outputs[0] << shift | low;
+        let mut candidate = Lcg::new(state, a, c, m);
+        let matches = candidate
+            .by_ref()
+            .take(outputs.len() - 1)
+            .map(|x| x >> shift)
+            .eq(outputs[1..].iter().copied());
+        matches.then_some(candidate)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_java_random() {
+        assert_eq!(java_next_int(&mut java_random(0)), -1155484576);
+        assert_eq!(java_next_int(&mut java_random(42)), -1170105035);
+    }
+
+    #[test]
+    fn test_recover_lcg() {
+        let mut lcg = Lcg::new(1234567, 0x1d2c3b4a5968, 0x77665544, (1 << 61) - 1);
+        let outputs: Vec<u64> = lcg.by_ref().take(8).collect();
+        assert_eq!(recover_modulus(&outputs), Some((1 << 61) - 1));
+        let mut recovered = recover_lcg(&outputs).unwrap();
+        assert_eq!(recovered, lcg);
+        assert_eq!(recovered.next_u64(), lcg.next_u64());
+
+        // glibc's rand_r style parameters with a power of two modulus
+        let mut lcg = Lcg::new(99, 1103515245, 12345, 1 << 31);
+        let outputs: Vec<u64> = lcg.by_ref().take(12).collect();
+        assert_eq!(recover_lcg(&outputs), Some(lcg));
+        assert_eq!(mod_inverse(4, 8), None);
+    }
+
+    #[test]
+    fn test_recover_truncated() {
+        let mut java = java_random(0xc0ffee);
+        let outputs: Vec<u64> = (0..3)
+            .map(|_| java_next_int(&mut java) as u32 as u64)
+            .collect();
+        let mut recovered =
+            recover_truncated(&outputs, 16, JAVA_MULTIPLIER, JAVA_INCREMENT, JAVA_MODULUS).unwrap();
+        for _ in 0..10 {
+            assert_eq!(java_next_int(&mut recovered), java_next_int(&mut java));
+        }
+    }
+}
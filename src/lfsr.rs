@@ -0,0 +1,212 @@
+use crate::gf2::{BitVec, Echelon};
+
+// Linear feedback shift registers of up to 64 bits, both ways round, with
+// Berlekamp-Massey to find the shortest one behind a bit sequence.
+
+// Fibonacci: output the low bit, shift right, feed the parity of the tapped
+// bits in at the top
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lfsr {
+    pub state: u64,
+    pub taps: u64,
+    pub length: u32,
+}
+
+impl Lfsr {
+    pub fn new(state: u64, taps: u64, length: u32) -> Self {
+        assert!((1..=64).contains(&length));
+        Lfsr {
+            state,
+            taps,
+            length,
+        }
+    }
+
+    pub fn next_bit(&mut self) -> bool {
+        let out = self.state & 1 == 1;
+        let feedback = (self.state & self.taps).count_ones() as u64 & 1;
+        self.state = self.state >> 1 | feedback << (self.length - 1);
+        out
+    }
+}
+
+impl Iterator for Lfsr {
+    type Item = bool;
+    fn next(&mut self) -> Option<bool> {
+        Some(self.next_bit())
+    }
+}
+
+// Galois: output the low bit, shift right, and xor the taps in if it was set
+#[derive(Clone, Debug, PartialEq)]
+pub struct GaloisLfsr {
+    pub state: u64,
+    pub taps: u64,
+}
+
+impl GaloisLfsr {
+    pub fn new(state: u64, taps: u64) -> Self {
+        GaloisLfsr { state, taps }
+    }
+
+    pub fn next_bit(&mut self) -> bool {
+        let out = self.state & 1 == 1;
+        self.state >>= 1;
+        if out {
+            self.state ^= self.taps;
+        }
+        out
+    }
+}
+
+impl Iterator for GaloisLfsr {
+    type Item = bool;
+    fn next(&mut self) -> Option<bool> {
+        Some(self.next_bit())
+    }
+}
+
+// The shortest connection polynomial c, with c[0] = 1, such that
+// s[n] = c[1] s[n-1] ^ ... ^ c[L] s[n-L] for the whole sequence. Its length
+// less one is the linear complexity L.
+pub fn berlekamp_massey(bits: &[bool]) -> Vec<bool> {
+    let mut c = vec![true];
+    let mut b = vec![true];
+    let mut l = 0;
+    let mut m = 1;
+    for n in 0..bits.len() {
+        let discrepancy = (1..=l).fold(bits[n], |d, i| d ^ (c[i] & bits[n - i]));
+        if !discrepancy {
+            m += 1;
+            continue;
+        }
+        let previous = c.clone();
+        if c.len() < b.len() + m {
+            c.resize(b.len() + m, false);
+        }
+        for (i, x) in b.iter().enumerate() {
+            c[i + m] ^= x;
+        }
+        if 2 * l <= n {
+            l = n + 1 - l;
+            b = previous;
+            m = 1;
+        } else {
+            m += 1;
+        }
+    }
+    c.truncate(l + 1);
+    c
+}
+
+// The Fibonacci register behind bits, continuing after them. Needs at
+// least twice its length in bits to be sure.
+pub fn recover_lfsr(bits: &[bool]) -> Option<Lfsr> {
+    let c = berlekamp_massey(bits);
+    let length = c.len() - 1;
+    if length == 0 || length > 64 || bits.len() < 2 * length {
+        return None;
+    }
+    // the bit fed in at the top is s[n+L], and state bit L-i holds s[n+L-i]
+    let taps = (1..=length)
+        .filter(|i| c[*i])
+        .fold(0u64, |taps, i| taps | 1 << (length - i));
+    let state = bits[..length]
+        .iter()
+        .enumerate()
+        .fold(0u64, |state, (i, x)| state | (*x as u64) << i);
+    let mut lfsr = Lfsr::new(state, taps, length as u32);
+    lfsr.by_ref()
+        .take(bits.len())
+        .eq(bits.iter().copied())
+        .then_some(lfsr)
+}
+
+// With the taps known, every output bit is linear in the initial state, so
+// solve for it. The result continues after the bits given.
+pub fn recover_galois_state(taps: u64, length: u32, bits: &[bool]) -> Option<GaloisLfsr> {
+    let length = length as usize;
+    let mut state: Vec<BitVec> = (0..length).map(|i| BitVec::unit(length, i)).collect();
+    let mut system = Echelon::new(length);
+    for bit in bits {
+        let out = state[0].clone();
+        system.insert(out.clone(), *bit).ok()?;
+        state.rotate_left(1);
+        state[length - 1] = BitVec::zeros(length);
+        for (i, x) in state.iter_mut().enumerate() {
+            if taps >> i & 1 == 1 {
+                *x ^= &out;
+            }
+        }
+    }
+    if system.rank() < length {
+        return None;
+    }
+    let mut lfsr = GaloisLfsr::new(system.solve().words()[0], taps);
+    for _ in bits {
+        lfsr.next_bit();
+    }
+    Some(lfsr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // both of Wikipedia's 16 bit examples, x^16 + x^14 + x^13 + x^11 + 1,
+    // have the maximum period
+    #[test]
+    fn test_period() {
+        let mut fibonacci = Lfsr::new(0xace1, 0b101101, 16);
+        let period = (1..).find(|_| {
+            fibonacci.next_bit();
+            fibonacci.state == 0xace1
+        });
+        assert_eq!(period, Some(65535));
+
+        let mut galois = GaloisLfsr::new(0xace1, 0xb400);
+        let period = (1..).find(|_| {
+            galois.next_bit();
+            galois.state == 0xace1
+        });
+        assert_eq!(period, Some(65535));
+    }
+
+    #[test]
+    fn test_berlekamp_massey() {
+        let bits: Vec<bool> = Lfsr::new(0xace1, 0b101101, 16).take(100).collect();
+        let c = berlekamp_massey(&bits);
+        assert_eq!(c.len() - 1, 16);
+        // s[n] = s[n-16] ^ s[n-14] ^ s[n-13] ^ s[n-11]
+        let taps: Vec<usize> = (1..c.len()).filter(|i| c[*i]).collect();
+        assert_eq!(taps, vec![11, 13, 14, 16]);
+
+        // a Galois register's output is a Fibonacci sequence too
+        let bits: Vec<bool> = GaloisLfsr::new(0xace1, 0xb400).take(100).collect();
+        assert_eq!(berlekamp_massey(&bits).len() - 1, 16);
+        assert_eq!(berlekamp_massey(&[false; 10]), vec![true]);
+    }
+
+    #[test]
+    fn test_recover_lfsr() {
+        let mut lfsr = Lfsr::new(0x1234_5678_9abc, 0x8000_0000_0465, 48);
+        let bits: Vec<bool> = lfsr.by_ref().take(200).collect();
+        let mut recovered = recover_lfsr(&bits).unwrap();
+        assert_eq!(
+            recovered.by_ref().take(500).collect::<Vec<bool>>(),
+            lfsr.by_ref().take(500).collect::<Vec<bool>>()
+        );
+        // too few bits, and a shorter register explains them just as well
+        assert!(recover_lfsr(&bits[..50]).unwrap().length < 48);
+        assert!(recover_lfsr(&[false; 10]).is_none());
+    }
+
+    #[test]
+    fn test_recover_galois_state() {
+        let mut galois = GaloisLfsr::new(0xbeef, 0xb400);
+        let bits: Vec<bool> = galois.by_ref().take(24).collect();
+        let recovered = recover_galois_state(0xb400, 16, &bits).unwrap();
+        assert_eq!(recovered, galois);
+        assert!(recover_galois_state(0xb400, 16, &bits[..8]).is_none());
+    }
+}
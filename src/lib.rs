@@ -1,4 +1,32 @@
 pub mod aes;
+pub mod bitflip;
 pub mod bytes;
+pub mod cbc_key_iv;
+pub mod combiner;
+pub mod cribdrag;
+pub mod ctr;
+pub mod ctr_edit;
+pub mod dfa;
 pub mod distance;
+pub mod ecb;
+pub mod fixed_nonce;
 pub mod frequency;
+pub mod gf2;
+pub mod gf256;
+pub mod keyfind;
+pub mod kv;
+pub mod lcg;
+pub mod lfsr;
+pub mod mt19937;
+pub mod mt_clone;
+pub mod mt_cypher;
+pub mod oracle;
+pub mod padding_oracle;
+pub mod power;
+pub mod profile;
+pub mod sbox;
+pub mod seed_crack;
+pub mod sha1;
+pub mod shamir;
+pub mod stream;
+pub mod xorshift;
@@ -0,0 +1,270 @@
+// The Mersenne Twister, MT19937 and MT19937-64, as in Matsumoto and
+// Nishimura's reference code. Twisting and tempering are separate so state
+// recovery can run them forwards and backwards.
+
+pub const N: usize = 624;
+pub const M: usize = 397;
+pub const MATRIX_A: u32 = 0x9908b0df;
+const UPPER_MASK: u32 = 0x80000000;
+const LOWER_MASK: u32 = 0x7fffffff;
+
+pub const fn temper(y: u32) -> u32 {
+    let mut y = y;
+    y ^= y >> 11;
+    y ^= (y << 7) & 0x9d2c5680;
+    y ^= (y << 15) & 0xefc60000;
+    y ^ (y >> 18)
+}
+
+// the next state word from the one being replaced, the one after it and the
+// one M along
+pub const fn twist_word(current: u32, next: u32, m_along: u32) -> u32 {
+    let y = (current & UPPER_MASK) | (next & LOWER_MASK);
+    let mag = if y & 1 == 1 { MATRIX_A } else { 0 };
+    m_along ^ (y >> 1) ^ mag
+}
+
+// regenerate the whole state in place, as the reference code does
+pub fn twist(state: &mut [u32; N]) {
+    for i in 0..N {
+        state[i] = twist_word(state[i], state[(i + 1) % N], state[(i + M) % N]);
+    }
+}
+
+#[derive(Clone)]
+pub struct Mt19937 {
+    pub state: [u32; N],
+    pub index: usize,
+}
+
+impl Mt19937 {
+    pub fn new(seed: u32) -> Self {
+        let mut state = [0u32; N];
+        state[0] = seed;
+        for i in 1..N {
+            state[i] = 1812433253u32
+                .wrapping_mul(state[i - 1] ^ (state[i - 1] >> 30))
+                .wrapping_add(i as u32);
+        }
+        Mt19937 { state, index: N }
+    }
+
+    pub fn init_by_array(key: &[u32]) -> Self {
+        assert!(!key.is_empty(), "init_by_array needs a non-empty key");
+        let mut mt = Mt19937::new(19650218);
+        let state = &mut mt.state;
+        let (mut i, mut j) = (1, 0);
+        for _ in 0..N.max(key.len()) {
+            state[i] = (state[i] ^ (state[i - 1] ^ (state[i - 1] >> 30)).wrapping_mul(1664525))
+                .wrapping_add(key[j])
+                .wrapping_add(j as u32);
+            i += 1;
+            j += 1;
+            if i >= N {
+                state[0] = state[N - 1];
+                i = 1;
+            }
+            if j >= key.len() {
+                j = 0;
+            }
+        }
+        for _ in 0..N - 1 {
+            state[i] = (state[i] ^ (state[i - 1] ^ (state[i - 1] >> 30)).wrapping_mul(1566083941))
+                .wrapping_sub(i as u32);
+            i += 1;
+            if i >= N {
+                state[0] = state[N - 1];
+                i = 1;
+            }
+        }
+        state[0] = 0x80000000;
+        mt
+    }
+
+    // a state to continue from, as if just after a twist
+    pub fn from_state(state: [u32; N]) -> Self {
+        Mt19937 { state, index: N }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        if self.index >= N {
+            twist(&mut self.state);
+            self.index = 0;
+        }
+        let y = self.state[self.index];
+        self.index += 1;
+        temper(y)
+    }
+}
+
+impl Iterator for Mt19937 {
+    type Item = u32;
+    fn next(&mut self) -> Option<u32> {
+        Some(self.next_u32())
+    }
+}
+
+pub const NN: usize = 312;
+pub const MM: usize = 156;
+pub const MATRIX_A_64: u64 = 0xb5026f5aa96619e9;
+const UPPER_MASK_64: u64 = 0xffffffff80000000;
+const LOWER_MASK_64: u64 = 0x7fffffff;
+
+pub const fn temper_64(y: u64) -> u64 {
+    let mut y = y;
+    y ^= (y >> 29) & 0x5555555555555555;
+    y ^= (y << 17) & 0x71d67fffeda60000;
+    y ^= (y << 37) & 0xfff7eee000000000;
+    y ^ (y >> 43)
+}
+
+pub const fn twist_word_64(current: u64, next: u64, m_along: u64) -> u64 {
+    let y = (current & UPPER_MASK_64) | (next & LOWER_MASK_64);
+    let mag = if y & 1 == 1 { MATRIX_A_64 } else { 0 };
+    m_along ^ (y >> 1) ^ mag
+}
+
+pub fn twist_64(state: &mut [u64; NN]) {
+    for i in 0..NN {
+        state[i] = twist_word_64(state[i], state[(i + 1) % NN], state[(i + MM) % NN]);
+    }
+}
+
+#[derive(Clone)]
+pub struct Mt19937_64 {
+    pub state: [u64; NN],
+    pub index: usize,
+}
+
+impl Mt19937_64 {
+    pub fn new(seed: u64) -> Self {
+        let mut state = [0u64; NN];
+        state[0] = seed;
+        for i in 1..NN {
+            state[i] = 6364136223846793005u64
+                .wrapping_mul(state[i - 1] ^ (state[i - 1] >> 62))
+                .wrapping_add(i as u64);
+        }
+        Mt19937_64 { state, index: NN }
+    }
+
+    pub fn init_by_array(key: &[u64]) -> Self {
+        assert!(!key.is_empty(), "init_by_array needs a non-empty key");
+        let mut mt = Mt19937_64::new(19650218);
+        let state = &mut mt.state;
+        let (mut i, mut j) = (1, 0);
+        for _ in 0..NN.max(key.len()) {
+            state[i] = (state[i]
+                ^ (state[i - 1] ^ (state[i - 1] >> 62)).wrapping_mul(3935559000370003845))
+            .wrapping_add(key[j])
+            .wrapping_add(j as u64);
+            i += 1;
+            j += 1;
+            if i >= NN {
+                state[0] = state[NN - 1];
+                i = 1;
+            }
+            if j >= key.len() {
+                j = 0;
+            }
+        }
+        for _ in 0..NN - 1 {
+            state[i] = (state[i]
+                ^ (state[i - 1] ^ (state[i - 1] >> 62)).wrapping_mul(2862933555777941757))
+            .wrapping_sub(i as u64);
+            i += 1;
+            if i >= NN {
+                state[0] = state[NN - 1];
+                i = 1;
+            }
+        }
+        state[0] = 1 << 63;
+        mt
+    }
+
+    pub fn from_state(state: [u64; NN]) -> Self {
+        Mt19937_64 { state, index: NN }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        if self.index >= NN {
+            twist_64(&mut self.state);
+            self.index = 0;
+        }
+        let y = self.state[self.index];
+        self.index += 1;
+        temper_64(y)
+    }
+}
+
+impl Iterator for Mt19937_64 {
+    type Item = u64;
+    fn next(&mut self) -> Option<u64> {
+        Some(self.next_u64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Exercise 21 solution
+    #[test]
+    fn test_mt19937() {
+        // the reference code's default seed, as used by C++ std::mt19937
+        let mut mt = Mt19937::new(5489);
+        assert_eq!(
+            [mt.next_u32(), mt.next_u32(), mt.next_u32()],
+            [3499211612, 581869302, 3890346734]
+        );
+        assert_eq!(Mt19937::new(5489).nth(9999), Some(4123659995));
+
+        // mt19937ar.out
+        let outputs: Vec<u32> = Mt19937::init_by_array(&[0x123, 0x234, 0x345, 0x456])
+            .take(5)
+            .collect();
+        assert_eq!(
+            outputs,
+            [1067595299, 955945823, 477289528, 4107218783, 4228976476]
+        );
+
+        // Python's random.seed(5489) seeds through init_by_array
+        let outputs: Vec<u32> = Mt19937::init_by_array(&[5489]).take(3).collect();
+        assert_eq!(outputs, [3382763572, 956215839, 417760592]);
+    }
+
+    #[test]
+    fn test_mt19937_64() {
+        assert_eq!(Mt19937_64::new(5489).nth(9999), Some(9981545732273789042));
+
+        // mt19937-64.out
+        let outputs: Vec<u64> = Mt19937_64::init_by_array(&[0x12345, 0x23456, 0x34567, 0x45678])
+            .take(5)
+            .collect();
+        assert_eq!(
+            outputs,
+            [
+                7266447313870364031,
+                4946485549665804864,
+                16945909448695747420,
+                16394063075524226720,
+                4873882236456199058
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "non-empty key")]
+    fn test_init_by_empty_array() {
+        Mt19937::init_by_array(&[]);
+    }
+
+    #[test]
+    fn test_from_state() {
+        let mut mt = Mt19937::new(42);
+        mt.next_u32();
+        let mut copy = Mt19937::from_state(mt.state);
+        mt.index = N;
+        assert_eq!(copy.next_u32(), mt.next_u32());
+    }
+}
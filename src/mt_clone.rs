@@ -0,0 +1,221 @@
+use crate::gf2::{BitVec, Echelon};
+use crate::mt19937::{temper, Mt19937, M, MATRIX_A, N};
+
+// Exercise 23: recovering MT19937's state from what it outputs.
+
+// Each tempering step xors y with a shifted, masked copy of itself. The bits
+// shifted in come from further along, so undo one shift width at a time.
+fn undo_right(y: u32, shift: u32) -> u32 {
+    let mut x = y;
+    for _ in 0..32 / shift {
+        x = y ^ (x >> shift);
+    }
+    x
+}
+
+fn undo_left(y: u32, shift: u32, mask: u32) -> u32 {
+    let mut x = y;
+    for _ in 0..32 / shift {
+        x = y ^ ((x << shift) & mask);
+    }
+    x
+}
+
+pub fn untemper(y: u32) -> u32 {
+    let y = undo_right(y, 18);
+    let y = undo_left(y, 15, 0xefc60000);
+    let y = undo_left(y, 7, 0x9d2c5680);
+    undo_right(y, 11)
+}
+
+// Any 624 outputs in a row are the whole state, untempered. The clone
+// carries on from the last output given.
+pub fn clone_from_outputs(outputs: &[u32]) -> Mt19937 {
+    assert!(outputs.len() >= N, "needs {} outputs in a row", N);
+    let mut state = [0u32; N];
+    for (x, y) in state.iter_mut().zip(outputs) {
+        *x = untemper(*y);
+    }
+    let mut clone = Mt19937 { state, index: 0 };
+    for _ in 0..outputs.len() {
+        clone.next_u32();
+    }
+    clone
+}
+
+// An output with only some bits seen: missing ones, or truncated ones like
+// Python's getrandbits(k), which keeps the top k bits.
+#[derive(Clone, Copy, Debug)]
+pub struct Observation {
+    pub value: u32,
+    pub known: u32,
+}
+
+impl Observation {
+    pub fn full(value: u32) -> Self {
+        Observation { value, known: !0 }
+    }
+
+    pub fn missing() -> Self {
+        Observation { value: 0, known: 0 }
+    }
+
+    pub fn top_bits(value: u32, bits: u32) -> Self {
+        assert!((1..=32).contains(&bits), "top_bits takes 1 to 32 bits");
+        let known = !0u32 << (32 - bits);
+        Observation {
+            value: value << (32 - bits),
+            known,
+        }
+    }
+}
+
+const UNKNOWNS: usize = 32 * N;
+
+// a state word with every bit written as a combination of the bits of the
+// first state words, the unknowns
+type Symbolic = Vec<BitVec>;
+
+fn unknown_word(word: usize) -> Symbolic {
+    (0..32)
+        .map(|bit| BitVec::unit(UNKNOWNS, 32 * word + bit))
+        .collect()
+}
+
+// twist_word, one output bit at a time
+fn symbolic_twist(current: &Symbolic, next: &Symbolic, m_along: &Symbolic) -> Symbolic {
+    let y = |bit: usize| if bit == 31 { &current[31] } else { &next[bit] };
+    (0..32)
+        .map(|bit| {
+            let mut combination = m_along[bit].clone();
+            if bit < 31 {
+                combination ^= y(bit + 1);
+            }
+            if MATRIX_A >> bit & 1 == 1 {
+                combination ^= y(0);
+            }
+            combination
+        })
+        .collect()
+}
+
+// Solve for the state over GF(2). Tempering and twisting are linear, so
+// every known output bit is an equation in the 19968 bits of the first 624
+// state words. Fails if the equations contradict each other or don't pin
+// down the 19937 bits that matter for what comes next.
+pub fn clone_from_observations(observations: &[Observation]) -> Option<Mt19937> {
+    // output bit i of temper(x) is the xor of the state bits j where
+    // temper(1 << j) has bit i set
+    let tempering: Vec<u32> = (0..32).map(|j| temper(1 << j)).collect();
+
+    let mut system = Echelon::new(UNKNOWNS);
+    let mut window: Vec<Symbolic> = (0..N).map(unknown_word).collect();
+    for (i, observation) in observations.iter().enumerate() {
+        if i >= N {
+            let slot = i % N;
+            window[slot] = symbolic_twist(
+                &window[slot],
+                &window[(slot + 1) % N],
+                &window[(slot + M) % N],
+            );
+        }
+        let word = &window[i % N];
+        for bit in (0..32).filter(|bit| observation.known >> bit & 1 == 1) {
+            let mut equation = BitVec::zeros(UNKNOWNS);
+            for (j, column) in tempering.iter().enumerate() {
+                if column >> bit & 1 == 1 {
+                    equation ^= &word[j];
+                }
+            }
+            system
+                .insert(equation, observation.value >> bit & 1 == 1)
+                .ok()?;
+        }
+    }
+    // the low 31 bits of the first word never reach the output, so rank
+    // alone can't say whether the rest is known
+    if !(31..UNKNOWNS).all(|unknown| system.has_pivot(unknown)) {
+        return None;
+    }
+
+    let solution = system.solve();
+    let mut state = [0u32; N];
+    for (i, x) in state.iter_mut().enumerate() {
+        *x = (solution.words()[i / 2] >> (32 * (i % 2))) as u32;
+    }
+    let mut clone = Mt19937 { state, index: 0 };
+    for _ in 0..observations.len() {
+        clone.next_u32();
+    }
+    Some(clone)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_untemper() {
+        for y in [0, 1, 0xdeadbeef, 0x80000000, 0xffffffff, 0x12345678] {
+            assert_eq!(untemper(temper(y)), y);
+        }
+    }
+
+    // Exercise 23 solution
+    #[test]
+    fn test_clone_from_outputs() {
+        let mut mt = Mt19937::new(23);
+        mt.nth(1000);
+        let outputs: Vec<u32> = mt.by_ref().take(700).collect();
+        let mut clone = clone_from_outputs(&outputs);
+        for _ in 0..2000 {
+            assert_eq!(clone.next_u32(), mt.next_u32());
+        }
+    }
+
+    #[test]
+    fn test_clone_with_gaps() {
+        // a missing word early on only shows up again 623 and 624 outputs
+        // later, split across two twisted words
+        let mut mt = Mt19937::new(2323);
+        let observations: Vec<Observation> = mt
+            .by_ref()
+            .take(1300)
+            .enumerate()
+            .map(|(i, x)| match i % 5 {
+                4 => Observation::missing(),
+                _ => Observation::full(x),
+            })
+            .collect();
+        let mut clone = clone_from_observations(&observations).unwrap();
+        for _ in 0..1000 {
+            assert_eq!(clone.next_u32(), mt.next_u32());
+        }
+        assert!(clone_from_observations(&observations[..624]).is_none());
+    }
+
+    #[test]
+    fn test_clone_truncated() {
+        // getrandbits(8), top byte of each output only
+        let mut mt = Mt19937::new(8);
+        let observations: Vec<Observation> = mt
+            .by_ref()
+            .take(3000)
+            .map(|x| Observation::top_bits(x >> 24, 8))
+            .collect();
+        let mut clone = clone_from_observations(&observations).unwrap();
+        for _ in 0..1000 {
+            assert_eq!(clone.next_u32(), mt.next_u32());
+        }
+
+        // by 2493 outputs the rank is full, but some of it is spent on bits
+        // of the first word that don't matter
+        assert!(clone_from_observations(&observations[..2493]).is_none());
+        let mut clone = clone_from_observations(&observations[..2494]).unwrap();
+        let mut mt = Mt19937::new(8);
+        mt.nth(2493);
+        for _ in 0..1000 {
+            assert_eq!(clone.next_u32(), mt.next_u32());
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::time::{Duration, Instant};
+
+use crate::mt19937::Mt19937;
+use crate::seed_crack::search;
+use crate::stream::StreamCypher;
+
+// Exercise 24's stream cypher: MT19937 seeded with a 16 bit key, one
+// keystream byte from the low byte of each output.
+pub struct MtCypher {
+    seed: u16,
+}
+
+impl MtCypher {
+    pub fn new(seed: u16) -> Self {
+        MtCypher { seed }
+    }
+}
+
+impl StreamCypher for MtCypher {
+    // MT can't seek, so everything before offset is generated and dropped
+    fn keystream(&self, offset: usize, length: usize) -> Vec<u8> {
+        Mt19937::new(self.seed as u32)
+            .skip(offset)
+            .take(length)
+            .map(|x| x as u8)
+            .collect()
+    }
+}
+
+pub struct SeedRecovery {
+    pub seed: u16,
+    pub elapsed: Duration,
+}
+
+// With the end of the plaintext known, a seed is right if it turns the end
+// of the cyphertext into it. All 65536 seeds, spread over threads.
+pub fn recover_seed(
+    cyphertext: &[u8],
+    known_suffix: &[u8],
+    threads: usize,
+) -> Option<SeedRecovery> {
+    assert!(known_suffix.len() <= cyphertext.len());
+    let start = Instant::now();
+    let offset = cyphertext.len() - known_suffix.len();
+    let seed = search(0..1 << 16, threads, |seed| {
+        MtCypher::new(seed as u16).apply(offset, &cyphertext[offset..]) == known_suffix
+    })?;
+    Some(SeedRecovery {
+        seed: seed as u16,
+        elapsed: start.elapsed(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bytes::ParseBytes;
+    use crate::cribdrag::Session;
+    use crate::fixed_nonce::break_common_keystream;
+    use crate::seed_crack::available_threads;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    // Exercise 24 solution
+    #[test]
+    fn test_recover_seed() {
+        let mut rng = StdRng::seed_from_u64(24);
+        let seed: u16 = rng.gen();
+        let cypher = MtCypher::new(seed);
+        let mut plaintext: Vec<u8> = (0..rng.gen_range(5..20)).map(|_| rng.gen()).collect();
+        plaintext.extend_from_slice(&[b'A'; 14]);
+        let cyphertext = cypher.apply(0, &plaintext);
+        assert_eq!(cypher.apply(0, &cyphertext), plaintext);
+
+        let recovery = recover_seed(&cyphertext, &[b'A'; 14], available_threads()).unwrap();
+        assert_eq!(recovery.seed, seed);
+        // the whole 16 bit seed space takes well under a second
+        assert!(recovery.elapsed < Duration::from_secs(1));
+        assert_eq!(
+            MtCypher::new(recovery.seed).apply(0, &cyphertext),
+            plaintext
+        );
+    }
+
+    #[test]
+    fn test_keystream_tools() {
+        let cypher = MtCypher::new(0x1916);
+        let mut cyphertext = cypher.apply(0, b"a terrible beauty is born");
+        cypher.edit(&mut cyphertext, 11, b"BEAUTY");
+        assert_eq!(
+            cypher.apply(2, &cyphertext[2..]),
+            b"terrible BEAUTY is born"
+        );
+
+        // reusing the seed is a many-time pad like any other
+        let cyphertexts: Vec<Vec<u8>> = include_str!("../data/19.txt")
+            .lines()
+            .map(|x| cypher.apply(0, &Vec::from_base64_byte_array(x.as_bytes()).unwrap()))
+            .collect();
+        let keystream = break_common_keystream(&cyphertexts);
+        assert_eq!(keystream[1..], cypher.keystream(1, 19));
+
+        let mut session = Session::new(cyphertexts);
+        session.place(0, 0, b"I have met them").unwrap();
+        assert_eq!(session.render(15), "A terrible beau___________");
+    }
+}
@@ -0,0 +1,196 @@
+use std::{marker::PhantomData, thread::sleep, time::Duration};
+
+// A black box an attack gets to query, and wrappers that count the queries,
+// keep a transcript, slow them down or cap how many are allowed.
+pub trait Oracle {
+    type Input: ?Sized;
+    type Output;
+    fn query(&mut self, input: &Self::Input) -> Self::Output;
+}
+
+impl<T: Oracle + ?Sized> Oracle for &mut T {
+    type Input = T::Input;
+    type Output = T::Output;
+    fn query(&mut self, input: &Self::Input) -> Self::Output {
+        (**self).query(input)
+    }
+}
+
+// closures can't implement Oracle directly, the output type would be
+// unconstrained, so they get wrapped with from_fn
+pub struct FnOracle<F, I: ?Sized, O> {
+    f: F,
+    _marker: PhantomData<fn(&I) -> O>,
+}
+
+pub fn from_fn<I: ?Sized, O, F: FnMut(&I) -> O>(f: F) -> FnOracle<F, I, O> {
+    FnOracle {
+        f,
+        _marker: PhantomData,
+    }
+}
+
+impl<F: FnMut(&I) -> O, I: ?Sized, O> Oracle for FnOracle<F, I, O> {
+    type Input = I;
+    type Output = O;
+    fn query(&mut self, input: &I) -> O {
+        (self.f)(input)
+    }
+}
+
+pub struct Counting<T> {
+    pub inner: T,
+    queries: usize,
+}
+
+impl<T: Oracle> Counting<T> {
+    pub fn new(inner: T) -> Self {
+        Counting { inner, queries: 0 }
+    }
+
+    pub fn queries(&self) -> usize {
+        self.queries
+    }
+}
+
+impl<T: Oracle> Oracle for Counting<T> {
+    type Input = T::Input;
+    type Output = T::Output;
+    fn query(&mut self, input: &Self::Input) -> Self::Output {
+        self.queries += 1;
+        self.inner.query(input)
+    }
+}
+
+pub struct Transcript<T: Oracle>
+where
+    T::Input: ToOwned,
+{
+    pub inner: T,
+    pub log: Vec<(<T::Input as ToOwned>::Owned, T::Output)>,
+}
+
+impl<T: Oracle> Transcript<T>
+where
+    T::Input: ToOwned,
+{
+    pub fn new(inner: T) -> Self {
+        Transcript { inner, log: vec![] }
+    }
+}
+
+impl<T: Oracle> Oracle for Transcript<T>
+where
+    T::Input: ToOwned,
+    T::Output: Clone,
+{
+    type Input = T::Input;
+    type Output = T::Output;
+    fn query(&mut self, input: &Self::Input) -> Self::Output {
+        let output = self.inner.query(input);
+        self.log.push((input.to_owned(), output.clone()));
+        output
+    }
+}
+
+pub struct Latency<T> {
+    pub inner: T,
+    pub delay: Duration,
+}
+
+impl<T: Oracle> Latency<T> {
+    pub fn new(inner: T, delay: Duration) -> Self {
+        Latency { inner, delay }
+    }
+}
+
+impl<T: Oracle> Oracle for Latency<T> {
+    type Input = T::Input;
+    type Output = T::Output;
+    fn query(&mut self, input: &Self::Input) -> Self::Output {
+        sleep(self.delay);
+        self.inner.query(input)
+    }
+}
+
+// Running out of budget panics, since an attack that needs more queries than
+// it's allowed has failed. try_query is there for attacks that can cope.
+pub struct Budget<T> {
+    pub inner: T,
+    remaining: usize,
+}
+
+impl<T: Oracle> Budget<T> {
+    pub fn new(inner: T, budget: usize) -> Self {
+        Budget {
+            inner,
+            remaining: budget,
+        }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    pub fn try_query(&mut self, input: &T::Input) -> Option<T::Output> {
+        self.remaining = self.remaining.checked_sub(1)?;
+        Some(self.inner.query(input))
+    }
+}
+
+impl<T: Oracle> Oracle for Budget<T> {
+    type Input = T::Input;
+    type Output = T::Output;
+    fn query(&mut self, input: &Self::Input) -> Self::Output {
+        self.try_query(input).expect("query budget exhausted")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn reverse(input: &[u8]) -> Vec<u8> {
+        input.iter().rev().copied().collect()
+    }
+
+    #[test]
+    fn test_wrappers() {
+        let mut oracle = Counting::new(Transcript::new(from_fn(reverse)));
+        assert_eq!(oracle.query(b"abc"), b"cba");
+        assert_eq!(oracle.query(b"xy"), b"yx");
+        assert_eq!(oracle.queries(), 2);
+        assert_eq!(
+            oracle.inner.log,
+            vec![
+                (b"abc".to_vec(), b"cba".to_vec()),
+                (b"xy".to_vec(), b"yx".to_vec())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_latency() {
+        let mut oracle = Latency::new(from_fn(reverse), Duration::from_millis(20));
+        let start = Instant::now();
+        oracle.query(b"abc");
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_budget() {
+        let mut oracle = Budget::new(from_fn(reverse), 2);
+        assert_eq!(oracle.try_query(b"a"), Some(b"a".to_vec()));
+        assert_eq!(oracle.query(b"b"), b"b");
+        assert_eq!(oracle.remaining(), 0);
+        assert_eq!(oracle.try_query(b"c"), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "query budget exhausted")]
+    fn test_budget_exhausted() {
+        let mut oracle = Budget::new(from_fn(reverse), 0);
+        oracle.query(b"a");
+    }
+}
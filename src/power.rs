@@ -0,0 +1,194 @@
+use rand::Rng;
+
+use crate::aes::SBOX;
+
+// Simulated power analysis of the AES first round. Each trace has one sample
+// per state byte, taken as SubBytes writes its output, leaking either the
+// Hamming weight of the output or the Hamming distance from the input it
+// overwrites, plus Gaussian noise.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LeakageModel {
+    HammingWeight,
+    HammingDistance,
+}
+
+impl LeakageModel {
+    // leakage of SubBytes on one byte, given its input (plaintext ^ key)
+    pub fn leak(&self, input: u8) -> f64 {
+        let output = SBOX[input as usize];
+        match self {
+            LeakageModel::HammingWeight => output.count_ones() as f64,
+            LeakageModel::HammingDistance => (input ^ output).count_ones() as f64,
+        }
+    }
+}
+
+// Box-Muller, since rand doesn't ship a normal distribution itself
+pub fn gaussian(rng: &mut impl Rng, sigma: f64) -> f64 {
+    let u1: f64 = 1.0 - rng.gen::<f64>(); // (0, 1], ln(0) is no good
+    let u2: f64 = rng.gen();
+    sigma * (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+pub fn simulate_trace(
+    key: &[u8],
+    plaintext: [u8; 16],
+    model: LeakageModel,
+    sigma: f64,
+    rng: &mut impl Rng,
+) -> Vec<f64> {
+    // round key 0 is the first 16 bytes of any AES key, so sample j belongs
+    // to key byte j
+    assert!(key.len() >= 16, "AES keys are at least 16 bytes");
+    plaintext
+        .iter()
+        .zip(key)
+        .map(|(p, k)| model.leak(p ^ k) + gaussian(rng, sigma))
+        .collect()
+}
+
+pub fn simulate_traces(
+    key: &[u8],
+    count: usize,
+    model: LeakageModel,
+    sigma: f64,
+    rng: &mut impl Rng,
+) -> (Vec<[u8; 16]>, Vec<Vec<f64>>) {
+    let plaintexts: Vec<[u8; 16]> = (0..count).map(|_| rng.gen()).collect();
+    let traces = plaintexts
+        .iter()
+        .map(|p| simulate_trace(key, *p, model, sigma, rng))
+        .collect();
+    (plaintexts, traces)
+}
+
+// Pearson correlation coefficient
+pub fn correlation(a: &[f64], b: &[f64]) -> f64 {
+    assert_eq!(a.len(), b.len());
+    let n = a.len() as f64;
+    let mean_a = a.iter().sum::<f64>() / n;
+    let mean_b = b.iter().sum::<f64>() / n;
+    let (mut cov, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        cov += (x - mean_a) * (y - mean_b);
+        var_a += (x - mean_a).powi(2);
+        var_b += (y - mean_b).powi(2);
+    }
+    if var_a == 0.0 || var_b == 0.0 {
+        return 0.0;
+    }
+    cov / (var_a * var_b).sqrt()
+}
+
+// Best key byte guess for position `byte`, and its correlation, taking the
+// strongest correlation over every sample in the traces
+pub fn cpa_key_byte(
+    plaintexts: &[[u8; 16]],
+    traces: &[Vec<f64>],
+    byte: usize,
+    model: LeakageModel,
+) -> (u8, f64) {
+    let samples: Vec<Vec<f64>> = (0..traces[0].len())
+        .map(|t| traces.iter().map(|trace| trace[t]).collect())
+        .collect();
+    (0..=0xff)
+        .map(|guess: u8| {
+            let hypothesis: Vec<f64> = plaintexts
+                .iter()
+                .map(|p| model.leak(p[byte] ^ guess))
+                .collect();
+            let best = samples
+                .iter()
+                .map(|sample| correlation(&hypothesis, sample).abs())
+                .fold(0.0, f64::max);
+            (guess, best)
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap()
+}
+
+pub fn cpa(plaintexts: &[[u8; 16]], traces: &[Vec<f64>], model: LeakageModel) -> [u8; 16] {
+    let mut key = [0u8; 16];
+    for (byte, k) in key.iter_mut().enumerate() {
+        *k = cpa_key_byte(plaintexts, traces, byte, model).0;
+    }
+    key
+}
+
+// Smallest trace count, stepping by `step` up to `max`, at which CPA gets the
+// whole key right, or None if it never does
+pub fn traces_needed(
+    key: &[u8],
+    model: LeakageModel,
+    sigma: f64,
+    step: usize,
+    max: usize,
+    rng: &mut impl Rng,
+) -> Option<usize> {
+    let (plaintexts, traces) = simulate_traces(key, max, model, sigma, rng);
+    (step..=max)
+        .step_by(step)
+        .find(|n| cpa(&plaintexts[..*n], &traces[..*n], model) == key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bytes::ParseBytes;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_gaussian() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let samples: Vec<f64> = (0..10000).map(|_| gaussian(&mut rng, 2.0)).collect();
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let variance =
+            samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+        assert!(mean.abs() < 0.1);
+        assert!((variance - 4.0).abs() < 0.2);
+    }
+
+    #[test]
+    fn test_noiseless_trace() {
+        let key = Vec::from_hex_byte_array(b"000102030405060708090a0b0c0d0e0f").unwrap();
+        let mut rng = StdRng::seed_from_u64(1);
+        let trace = simulate_trace(&key, [0u8; 16], LeakageModel::HammingWeight, 0.0, &mut rng);
+        let expected: Vec<f64> = key
+            .iter()
+            .map(|k| SBOX[*k as usize].count_ones() as f64)
+            .collect();
+        assert_eq!(trace, expected);
+
+        // a longer key starts with the same first round key
+        let long_key = [key.clone(), vec![0xff; 16]].concat();
+        let trace = simulate_trace(
+            &long_key,
+            [0u8; 16],
+            LeakageModel::HammingWeight,
+            0.0,
+            &mut rng,
+        );
+        assert_eq!(trace, expected);
+    }
+
+    #[test]
+    fn test_cpa() {
+        let key = Vec::from_hex_byte_array(b"2b7e151628aed2a6abf7158809cf4f3c").unwrap();
+        let mut rng = StdRng::seed_from_u64(2);
+        for model in [LeakageModel::HammingWeight, LeakageModel::HammingDistance] {
+            let (plaintexts, traces) = simulate_traces(&key, 200, model, 1.0, &mut rng);
+            assert_eq!(cpa(&plaintexts, &traces, model).to_vec(), key);
+        }
+    }
+
+    #[test]
+    fn test_more_noise_needs_more_traces() {
+        let key = Vec::from_hex_byte_array(b"2b7e151628aed2a6abf7158809cf4f3c").unwrap();
+        let mut rng = StdRng::seed_from_u64(3);
+        let model = LeakageModel::HammingWeight;
+        let quiet = traces_needed(&key, model, 0.5, 10, 200, &mut rng).unwrap();
+        let noisy = traces_needed(&key, model, 2.0, 10, 200, &mut rng).unwrap();
+        assert!(quiet < noisy);
+    }
+}
@@ -0,0 +1,111 @@
+use rand::Rng;
+
+use crate::aes::{decrypt_aes_128_ecb, encrypt_aes_128_ecb};
+use crate::bytes::{pkcs7_pad, pkcs7_unpad};
+use crate::kv::{encode, parse};
+use crate::oracle::Oracle;
+
+// Exercise 13: user profiles as encrypted k=v cookies under AES-128-ECB.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProfileError {
+    BadLength,
+    BadPadding,
+    BadEncoding,
+}
+
+pub struct ProfileService {
+    key: [u8; 16],
+}
+
+impl ProfileService {
+    pub fn new(rng: &mut impl Rng) -> Self {
+        ProfileService { key: rng.gen() }
+    }
+
+    pub fn profile_for(&self, email: &str) -> Vec<u8> {
+        let profile = encode(&[
+            ("email".to_string(), email.to_string()),
+            ("uid".to_string(), "10".to_string()),
+            ("role".to_string(), "user".to_string()),
+        ]);
+        encrypt_aes_128_ecb(&self.key, &pkcs7_pad(profile.as_bytes(), 16))
+    }
+
+    pub fn decrypt_profile(
+        &self,
+        cyphertext: &[u8],
+    ) -> Result<Vec<(String, String)>, ProfileError> {
+        if !cyphertext.len().is_multiple_of(16) {
+            return Err(ProfileError::BadLength);
+        }
+        let padded = decrypt_aes_128_ecb(&self.key, cyphertext);
+        let plaintext = pkcs7_unpad(&padded, 16).map_err(|_| ProfileError::BadPadding)?;
+        let encoded = String::from_utf8(plaintext).map_err(|_| ProfileError::BadEncoding)?;
+        parse(&encoded).map_err(|_| ProfileError::BadEncoding)
+    }
+}
+
+impl Oracle for ProfileService {
+    type Input = str;
+    type Output = Vec<u8>;
+    fn query(&mut self, email: &str) -> Vec<u8> {
+        self.profile_for(email)
+    }
+}
+
+// Escaping keeps the metacharacters out, but not whole blocks. With the
+// cookie layout known, encrypt "admin" plus its padding as a block on its
+// own, then an email that leaves "role=" at the end of a block, and splice.
+pub fn forge_admin_profile(oracle: &mut impl Oracle<Input = str, Output = Vec<u8>>) -> Vec<u8> {
+    const BEFORE_EMAIL: usize = "email=".len();
+    const AFTER_EMAIL: usize = "&uid=10&role=".len();
+
+    let mut admin_email = "A".repeat(16 - BEFORE_EMAIL);
+    let admin_block = pkcs7_pad(b"admin", 16);
+    admin_email.push_str(std::str::from_utf8(&admin_block).unwrap());
+    let admin_cyphertext = oracle.query(&admin_email);
+
+    let email_length = 32 - BEFORE_EMAIL - AFTER_EMAIL;
+    let email = format!("{}@evil.com", "a".repeat(email_length - "@evil.com".len()));
+    let mut forged = oracle.query(&email)[..32].to_vec();
+    forged.extend_from_slice(&admin_cyphertext[16..32]);
+    forged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kv::get;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_profile_for() {
+        let mut rng = StdRng::seed_from_u64(13);
+        let service = ProfileService::new(&mut rng);
+        let profile = service
+            .decrypt_profile(&service.profile_for("foo@bar.com&role=admin"))
+            .unwrap();
+        assert_eq!(get(&profile, "email"), Some("foo@bar.com&role=admin"));
+        assert_eq!(get(&profile, "role"), Some("user"));
+        assert_eq!(
+            service.decrypt_profile(&[0u8; 16]),
+            Err(ProfileError::BadPadding)
+        );
+        assert_eq!(
+            service.decrypt_profile(&[0u8; 20]),
+            Err(ProfileError::BadLength)
+        );
+    }
+
+    // Exercise 13 solution
+    #[test]
+    fn test_forge_admin_profile() {
+        let mut rng = StdRng::seed_from_u64(13);
+        let mut service = ProfileService::new(&mut rng);
+        let forged = forge_admin_profile(&mut service);
+        let profile = service.decrypt_profile(&forged).unwrap();
+        assert_eq!(get(&profile, "email"), Some("aaaa@evil.com"));
+        assert_eq!(get(&profile, "role"), Some("admin"));
+    }
+}
@@ -0,0 +1,147 @@
+// Cryptanalytic properties of 4-bit and 8-bit S-boxes, given as a lookup
+// table of 16 or 256 entries.
+
+fn input_bits(sbox: &[u8]) -> u32 {
+    assert!(
+        sbox.len() == 16 || sbox.len() == 256,
+        "only 4-bit and 8-bit S-boxes"
+    );
+    sbox.len().trailing_zeros()
+}
+
+fn parity(x: usize) -> usize {
+    x.count_ones() as usize & 1
+}
+
+// ddt[a][b] counts the inputs x with S(x) ^ S(x ^ a) == b
+pub fn difference_distribution_table(sbox: &[u8]) -> Vec<Vec<usize>> {
+    let n = 1 << input_bits(sbox);
+    let mut ddt = vec![vec![0usize; n]; n];
+    for (a, row) in ddt.iter_mut().enumerate() {
+        for x in 0..n {
+            row[(sbox[x] ^ sbox[x ^ a]) as usize] += 1;
+        }
+    }
+    ddt
+}
+
+// lat[a][b] is how many inputs x have a.x == b.S(x), less n / 2, so zero means
+// the linear approximation holds no better than chance
+pub fn linear_approximation_table(sbox: &[u8]) -> Vec<Vec<i32>> {
+    let n = 1 << input_bits(sbox);
+    let mut lat = vec![vec![0i32; n]; n];
+    for (a, row) in lat.iter_mut().enumerate() {
+        for (b, entry) in row.iter_mut().enumerate() {
+            let agree = (0..n)
+                .filter(|x| parity(a & x) == parity(b & sbox[*x] as usize))
+                .count();
+            *entry = agree as i32 - n as i32 / 2;
+        }
+    }
+    lat
+}
+
+// largest DDT entry for a nonzero input difference
+pub fn differential_uniformity(sbox: &[u8]) -> usize {
+    difference_distribution_table(sbox)
+        .iter()
+        .skip(1)
+        .flat_map(|row| row.iter())
+        .copied()
+        .max()
+        .unwrap()
+}
+
+// distance from the nearest affine function over all nonzero output masks
+pub fn nonlinearity(sbox: &[u8]) -> usize {
+    let n = 1 << input_bits(sbox);
+    let max_bias = linear_approximation_table(sbox)
+        .iter()
+        .flat_map(|row| row.iter().skip(1))
+        .map(|x| x.unsigned_abs() as usize)
+        .max()
+        .unwrap();
+    n / 2 - max_bias
+}
+
+// algebraic normal form of one output bit, by the Moebius transform: anf[u]
+// is the coefficient of the monomial with the variables set in u
+fn algebraic_normal_form(truth_table: &[u8]) -> Vec<u8> {
+    let mut anf = truth_table.to_vec();
+    let mut step = 1;
+    while step < anf.len() {
+        for x in 0..anf.len() {
+            if x & step != 0 {
+                anf[x] ^= anf[x ^ step];
+            }
+        }
+        step <<= 1;
+    }
+    anf
+}
+
+// highest degree monomial in the algebraic normal form of any output bit
+pub fn algebraic_degree(sbox: &[u8]) -> u32 {
+    let m = input_bits(sbox);
+    (0..m)
+        .map(|bit| {
+            let truth_table: Vec<u8> = sbox.iter().map(|y| (y >> bit) & 1).collect();
+            algebraic_normal_form(&truth_table)
+                .iter()
+                .enumerate()
+                .filter(|(_, coefficient)| **coefficient == 1)
+                .map(|(u, _)| u.count_ones())
+                .max()
+                .unwrap_or(0)
+        })
+        .max()
+        .unwrap()
+}
+
+pub fn fixed_points(sbox: &[u8]) -> Vec<u8> {
+    let n = 1 << input_bits(sbox);
+    (0..n)
+        .filter(|x| sbox[*x] as usize == *x)
+        .map(|x| x as u8)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes::SBOX;
+
+    // Bogdanov et al., "PRESENT: An Ultra-Lightweight Block Cipher"
+    const PRESENT_SBOX: [u8; 16] = [
+        0xc, 0x5, 0x6, 0xb, 0x9, 0x0, 0xa, 0xd, 0x3, 0xe, 0xf, 0x8, 0x4, 0x7, 0x1, 0x2,
+    ];
+
+    #[test]
+    fn test_aes_sbox() {
+        assert_eq!(differential_uniformity(&SBOX), 4);
+        assert_eq!(nonlinearity(&SBOX), 112);
+        assert_eq!(algebraic_degree(&SBOX), 7);
+        assert_eq!(fixed_points(&SBOX), vec![]);
+    }
+
+    #[test]
+    fn test_present_sbox() {
+        let ddt = difference_distribution_table(&PRESENT_SBOX);
+        assert_eq!(ddt[0][0], 16);
+        assert!(ddt.iter().all(|row| row.iter().sum::<usize>() == 16));
+        assert_eq!(differential_uniformity(&PRESENT_SBOX), 4);
+        assert_eq!(nonlinearity(&PRESENT_SBOX), 4);
+        assert_eq!(algebraic_degree(&PRESENT_SBOX), 3);
+        assert_eq!(fixed_points(&PRESENT_SBOX), vec![]);
+    }
+
+    #[test]
+    fn test_identity_sbox() {
+        let identity: Vec<u8> = (0..16).collect();
+        assert_eq!(differential_uniformity(&identity), 16);
+        assert_eq!(nonlinearity(&identity), 0);
+        assert_eq!(algebraic_degree(&identity), 1);
+        assert_eq!(fixed_points(&identity), identity);
+        assert_eq!(linear_approximation_table(&identity)[5][5], 8);
+    }
+}
@@ -0,0 +1,113 @@
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::mt19937::Mt19937;
+
+// Exercises 22 and 24: MT19937 seeds small or guessable enough to search.
+// The 16 bit keyed stream cypher is in mt_cypher.
+
+pub fn available_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, |x| x.get())
+}
+
+// A seed for which matches holds, the range split evenly over threads that
+// all stop once one finds it.
+pub fn search(
+    seeds: Range<u32>,
+    threads: usize,
+    matches: impl Fn(u32) -> bool + Sync,
+) -> Option<u32> {
+    let found = AtomicBool::new(false);
+    let length = seeds.end.saturating_sub(seeds.start) as u64;
+    let chunk = length.div_ceil(threads.max(1) as u64).max(1);
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.max(1) as u64)
+            .map(|i| {
+                let start = seeds.start as u64 + i * chunk;
+                let end = (start + chunk).min(seeds.end as u64);
+                let (found, matches) = (&found, &matches);
+                scope.spawn(move || {
+                    for seed in start..end {
+                        if found.load(Ordering::Relaxed) {
+                            return None;
+                        }
+                        if matches(seed as u32) {
+                            found.store(true, Ordering::Relaxed);
+                            return Some(seed as u32);
+                        }
+                    }
+                    None
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .filter_map(|x| x.join().expect("search thread panicked"))
+            .min()
+    })
+}
+
+// Exercise 22: outputs are the first ones after seeding with a UNIX time
+// somewhere in window.
+pub fn crack_time_seed(outputs: &[u32], window: Range<u32>, threads: usize) -> Option<u32> {
+    search(window, threads, |seed| {
+        Mt19937::new(seed).zip(outputs).all(|(x, y)| x == *y)
+    })
+}
+
+pub fn reset_token(seed: u32, length: usize) -> Vec<u8> {
+    Mt19937::new(seed).take(length).map(|x| x as u8).collect()
+}
+
+// the seed if token came from an MT seeded with a time in window
+pub fn find_token_seed(token: &[u8], window: Range<u32>, threads: usize) -> Option<u32> {
+    search(window, threads, |seed| {
+        Mt19937::new(seed).zip(token).all(|(x, y)| x as u8 == *y)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    const NOW: u32 = 1_700_000_000;
+
+    #[test]
+    fn test_search() {
+        assert_eq!(search(0..1000, 7, |x| x == 999), Some(999));
+        assert_eq!(search(0..1000, 7, |x| x == 1000), None);
+        assert_eq!(search(5..6, 16, |x| x == 5), Some(5));
+        assert_eq!(search(0..0, 4, |_| true), None);
+    }
+
+    // Exercise 22 solution
+    #[test]
+    fn test_crack_time_seed() {
+        let mut rng = StdRng::seed_from_u64(22);
+        // seeded somewhere between 40 and 1000 seconds ago
+        let seed = NOW - rng.gen_range(40..=1000);
+        let output = Mt19937::new(seed).next_u32();
+        let window = NOW - 24 * 60 * 60..NOW + 1;
+        assert_eq!(
+            crack_time_seed(&[output], window, available_threads()),
+            Some(seed)
+        );
+    }
+
+    #[test]
+    fn test_find_token_seed() {
+        let mut rng = StdRng::seed_from_u64(2424);
+        let window = NOW - 60 * 60..NOW + 1;
+        let token = reset_token(NOW - 1234, 16);
+        assert_eq!(
+            find_token_seed(&token, window.clone(), available_threads()),
+            Some(NOW - 1234)
+        );
+        let random_token: Vec<u8> = (0..16).map(|_| rng.gen()).collect();
+        assert_eq!(
+            find_token_seed(&random_token, window, available_threads()),
+            None
+        );
+    }
+}
@@ -0,0 +1,225 @@
+// SHA-1, FIPS 180-4. The chaining state and the count of bytes hashed so
+// far can both be set, to carry on from a digest as length extension does.
+
+pub const INITIAL_STATE: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+pub fn compress(state: &mut [u32; 5], block: &[u8; 64]) {
+    let mut w = [0u32; 80];
+    for (i, word) in block.chunks(4).enumerate() {
+        w[i] = u32::from_be_bytes(word.try_into().unwrap());
+    }
+    for t in 16..80 {
+        w[t] = (w[t - 3] ^ w[t - 8] ^ w[t - 14] ^ w[t - 16]).rotate_left(1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e] = *state;
+    for (t, w) in w.iter().enumerate() {
+        let (f, k) = match t {
+            0..=19 => ((b & c) | (!b & d), 0x5a827999),
+            20..=39 => (b ^ c ^ d, 0x6ed9eba1),
+            40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+            _ => (b ^ c ^ d, 0xca62c1d6),
+        };
+        let temp = a
+            .rotate_left(5)
+            .wrapping_add(f)
+            .wrapping_add(e)
+            .wrapping_add(k)
+            .wrapping_add(*w);
+        e = d;
+        d = c;
+        c = b.rotate_left(30);
+        b = a;
+        a = temp;
+    }
+    for (x, y) in state.iter_mut().zip([a, b, c, d, e]) {
+        *x = x.wrapping_add(y);
+    }
+}
+
+// what finalize appends to a message of length bytes: 0x80, zeros up to 56
+// mod 64, then the length in bits
+pub fn padding(length: u64) -> Vec<u8> {
+    let zeros = (119 - length % 64) % 64;
+    let mut padding = vec![0x80];
+    padding.resize(1 + zeros as usize, 0);
+    padding.extend_from_slice(&(length * 8).to_be_bytes());
+    padding
+}
+
+pub fn state_from_digest(digest: &[u8; 20]) -> [u32; 5] {
+    let mut state = [0u32; 5];
+    for (x, word) in state.iter_mut().zip(digest.chunks(4)) {
+        *x = u32::from_be_bytes(word.try_into().unwrap());
+    }
+    state
+}
+
+#[derive(Clone)]
+pub struct Sha1 {
+    state: [u32; 5],
+    length: u64,
+    buffer: Vec<u8>,
+}
+
+impl Default for Sha1 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sha1 {
+    pub fn new() -> Self {
+        Sha1::from_state(INITIAL_STATE, 0)
+    }
+
+    // carry on as if length bytes had already gone in, leaving state; blocks
+    // are then cut from whatever update is given
+    pub fn from_state(state: [u32; 5], length: u64) -> Self {
+        Sha1 {
+            state,
+            length,
+            buffer: Vec::with_capacity(64),
+        }
+    }
+
+    pub fn state(&self) -> [u32; 5] {
+        self.state
+    }
+
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.length += data.len() as u64;
+        let mut data = data;
+        if !self.buffer.is_empty() {
+            let take = data.len().min(64 - self.buffer.len());
+            self.buffer.extend_from_slice(&data[..take]);
+            data = &data[take..];
+            if self.buffer.len() < 64 {
+                return;
+            }
+            compress(&mut self.state, self.buffer.as_slice().try_into().unwrap());
+            self.buffer.clear();
+        }
+        let mut blocks = data.chunks_exact(64);
+        for block in blocks.by_ref() {
+            compress(&mut self.state, block.try_into().unwrap());
+        }
+        self.buffer.extend_from_slice(blocks.remainder());
+    }
+
+    pub fn finalize(mut self) -> [u8; 20] {
+        // pad to the block boundary of this hash's own data, but encode the
+        // whole length
+        let mut padding = padding(self.buffer.len() as u64);
+        let length = padding.len() - 8;
+        padding[length..].copy_from_slice(&(self.length * 8).to_be_bytes());
+        let length = self.length;
+        self.update(&padding);
+        self.length = length;
+
+        let mut digest = [0u8; 20];
+        for (bytes, word) in digest.chunks_mut(4).zip(self.state) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+}
+
+pub fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut hash = Sha1::new();
+    hash.update(data);
+    hash.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bytes::RenderBytes;
+
+    fn hex(digest: [u8; 20]) -> String {
+        String::from_utf8(digest.to_vec().to_hex_byte_vec()).unwrap()
+    }
+
+    #[test]
+    fn test_sha1() {
+        assert_eq!(hex(sha1(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(
+            hex(sha1(b"abc")),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            hex(sha1(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+        );
+        assert_eq!(
+            hex(sha1(b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu")),
+            "a49b2446a02c645bf419f995b67091253a04a259"
+        );
+    }
+
+    #[test]
+    fn test_million_a() {
+        let mut hash = Sha1::new();
+        let chunk = [b'a'; 1000];
+        for _ in 0..1000 {
+            hash.update(&chunk);
+        }
+        assert_eq!(hash.length(), 1_000_000);
+        assert_eq!(
+            hex(hash.finalize()),
+            "34aa973cd4c4daa4f61eeb2bdbad27316534016f"
+        );
+    }
+
+    #[test]
+    fn test_streaming() {
+        let data: Vec<u8> = (0..1000u32).map(|x| (x * 7 % 251) as u8).collect();
+        let mut hash = Sha1::new();
+        let mut rest = data.as_slice();
+        for size in [0, 1, 63, 64, 65, 127, 5, 200].iter().cycle() {
+            if rest.is_empty() {
+                break;
+            }
+            let (chunk, remainder) = rest.split_at((*size).min(rest.len()));
+            hash.update(chunk);
+            rest = remainder;
+        }
+        assert_eq!(hash.finalize(), sha1(&data));
+    }
+
+    #[test]
+    fn test_padding() {
+        assert_eq!(padding(0).len(), 64);
+        assert_eq!(padding(55).len(), 9);
+        assert_eq!(padding(56).len(), 72);
+        assert_eq!(padding(3)[58..], [0, 0, 24]);
+    }
+
+    #[test]
+    fn test_from_state() {
+        // resuming after whole blocks matches hashing straight through
+        let data = [0x5au8; 200];
+        let mut first = Sha1::new();
+        first.update(&data[..128]);
+        let mut resumed = Sha1::from_state(first.state(), 128);
+        resumed.update(&data[128..]);
+        assert_eq!(resumed.finalize(), sha1(&data));
+
+        // and from a finished digest, the glue padding included
+        let message = b"comment1=cooking%20MCs;userdata=foo";
+        let digest = sha1(message);
+        let glued = message.len() as u64 + padding(message.len() as u64).len() as u64;
+        let mut extended = Sha1::from_state(state_from_digest(&digest), glued);
+        extended.update(b";admin=true");
+        let mut forged = message.to_vec();
+        forged.extend_from_slice(&padding(message.len() as u64));
+        forged.extend_from_slice(b";admin=true");
+        assert_eq!(extended.finalize(), sha1(&forged));
+    }
+}
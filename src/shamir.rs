@@ -0,0 +1,241 @@
+use rand::Rng;
+
+use crate::gf256::{Gf256, Inv};
+
+// Shamir k-of-n secret sharing over GF(2^8), one random polynomial of degree
+// k - 1 per secret byte, with the secret byte as its constant term.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Share {
+    pub x: u8,
+    pub y: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShareError {
+    NoShares,
+    ZeroX,
+    DuplicateX,
+    LengthMismatch,
+    NotEnoughShares,
+    // the shares don't all lie on one polynomial; the x of every share off
+    // the best fitting one, which are the cheaters if few enough of them
+    Inconsistent(Vec<u8>),
+}
+
+fn evaluate(coefficients: &[Gf256], x: Gf256) -> Gf256 {
+    // Horner's rule, highest coefficient first
+    coefficients
+        .iter()
+        .rev()
+        .fold(Gf256::ZERO, |acc, c| acc * x + *c)
+}
+
+pub fn split_secret(secret: &[u8], k: usize, n: usize, rng: &mut impl Rng) -> Vec<Share> {
+    assert!(0 < k && k <= n && n < 256);
+    let mut shares: Vec<Share> = (1..=n)
+        .map(|x| Share {
+            x: x as u8,
+            y: Vec::with_capacity(secret.len()),
+        })
+        .collect();
+    for byte in secret {
+        let mut coefficients = vec![Gf256(*byte)];
+        coefficients.extend((1..k).map(|_| Gf256(rng.gen())));
+        for share in shares.iter_mut() {
+            share.y.push(evaluate(&coefficients, Gf256(share.x)).0);
+        }
+    }
+    shares
+}
+
+fn check_shares(shares: &[Share]) -> Result<(), ShareError> {
+    let first = shares.first().ok_or(ShareError::NoShares)?;
+    for (i, share) in shares.iter().enumerate() {
+        if share.x == 0 {
+            return Err(ShareError::ZeroX);
+        }
+        if share.y.len() != first.y.len() {
+            return Err(ShareError::LengthMismatch);
+        }
+        if shares[..i].iter().any(|other| other.x == share.x) {
+            return Err(ShareError::DuplicateX);
+        }
+    }
+    Ok(())
+}
+
+// Lagrange basis polynomial for shares[i], evaluated at x
+fn lagrange_basis(shares: &[Share], i: usize, x: Gf256) -> Gf256 {
+    let x_i = Gf256(shares[i].x);
+    shares
+        .iter()
+        .enumerate()
+        .filter(|(j, _)| *j != i)
+        .map(|(_, share)| (x - Gf256(share.x)) * (x_i - Gf256(share.x)).inv())
+        .product()
+}
+
+// value at x of the lowest degree polynomial through all the shares
+pub fn interpolate_at(shares: &[Share], x: u8) -> Result<Vec<u8>, ShareError> {
+    check_shares(shares)?;
+    let basis: Vec<Gf256> = (0..shares.len())
+        .map(|i| lagrange_basis(shares, i, Gf256(x)))
+        .collect();
+    Ok((0..shares[0].y.len())
+        .map(|byte| {
+            shares
+                .iter()
+                .zip(&basis)
+                .map(|(share, l)| Gf256(share.y[byte]) * *l)
+                .sum::<Gf256>()
+                .0
+        })
+        .collect())
+}
+
+// Any k of the shares give back the secret. Fewer give a wrong answer rather
+// than an error, since nothing in a share says what k was.
+pub fn combine_shares(shares: &[Share]) -> Result<Vec<u8>, ShareError> {
+    interpolate_at(shares, 0)
+}
+
+// A cheater who knows which other shares will be combined with theirs can
+// shift the recovered secret by any xor difference they like, and afterwards
+// recover the real secret from the wrong one. This is the share to hand in.
+pub fn forge_share(share: &Share, other_xs: &[u8], shift: &[u8]) -> Share {
+    assert_eq!(share.y.len(), shift.len());
+    let mut all: Vec<Share> = vec![share.clone()];
+    all.extend(other_xs.iter().map(|x| Share {
+        x: *x,
+        y: vec![0; shift.len()],
+    }));
+    // the secret is linear in each y, with this cheater's basis value as slope
+    let slope = lagrange_basis(&all, 0, Gf256::ZERO);
+    Share {
+        x: share.x,
+        y: share
+            .y
+            .iter()
+            .zip(shift)
+            .map(|(y, s)| (Gf256(*y) + Gf256(*s) / slope).0)
+            .collect(),
+    }
+}
+
+fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    if k == 0 {
+        return vec![vec![]];
+    }
+    (k - 1..n)
+        .flat_map(|last| {
+            combinations(last, k - 1).into_iter().map(move |mut c| {
+                c.push(last);
+                c
+            })
+        })
+        .collect()
+}
+
+// Combine more than k shares, checking they all agree. With k + 2t shares
+// up to t cheaters are outvoted, and named in the error.
+pub fn combine_checked(shares: &[Share], k: usize) -> Result<Vec<u8>, ShareError> {
+    assert!(0 < k, "needs a threshold of at least one share");
+    check_shares(shares)?;
+    if shares.len() < k {
+        return Err(ShareError::NotEnoughShares);
+    }
+    let mut best: Option<Vec<u8>> = None;
+    for subset in combinations(shares.len(), k) {
+        let chosen: Vec<Share> = subset.iter().map(|i| shares[*i].clone()).collect();
+        let mut off_polynomial = vec![];
+        for share in shares {
+            if interpolate_at(&chosen, share.x)? != share.y {
+                off_polynomial.push(share.x);
+            }
+        }
+        if off_polynomial.is_empty() {
+            return combine_shares(&chosen);
+        }
+        if best.as_ref().is_none_or(|b| off_polynomial.len() < b.len()) {
+            best = Some(off_polynomial);
+        }
+    }
+    Err(ShareError::Inconsistent(best.unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_split_and_combine() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let secret = b"YELLOW SUBMARINE";
+        let shares = split_secret(secret, 3, 5, &mut rng);
+        assert_eq!(shares.len(), 5);
+        for subset in combinations(5, 3) {
+            let chosen: Vec<Share> = subset.iter().map(|i| shares[*i].clone()).collect();
+            assert_eq!(combine_shares(&chosen).unwrap(), secret);
+        }
+        assert_eq!(combine_shares(&shares).unwrap(), secret);
+        assert_ne!(combine_shares(&shares[..2]).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_bad_shares() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut shares = split_secret(b"secret", 2, 3, &mut rng);
+        assert_eq!(combine_shares(&[]), Err(ShareError::NoShares));
+        assert_eq!(
+            combine_shares(&[shares[0].clone(), shares[0].clone()]),
+            Err(ShareError::DuplicateX)
+        );
+        shares[1].y.pop();
+        assert_eq!(combine_shares(&shares), Err(ShareError::LengthMismatch));
+        assert_eq!(
+            combine_checked(&shares[..1], 2),
+            Err(ShareError::NotEnoughShares)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one share")]
+    fn test_combine_checked_zero_threshold() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let shares = split_secret(b"secret", 2, 3, &mut rng);
+        let _ = combine_checked(&shares, 0);
+    }
+
+    #[test]
+    fn test_cheating() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let secret = b"attack at dawn";
+        let shares = split_secret(secret, 3, 7, &mut rng);
+
+        // the cheater hands in a forged share alongside shares 2 and 3
+        let shift: Vec<u8> = b"attack at dawn"
+            .iter()
+            .zip(b"retreat at six")
+            .map(|(a, b)| a ^ b)
+            .collect();
+        let forged = forge_share(&shares[0], &[2, 3], &shift);
+        let fooled =
+            combine_shares(&[forged.clone(), shares[1].clone(), shares[2].clone()]).unwrap();
+        assert_eq!(fooled, b"retreat at six");
+        // and knowing the shift, the cheater alone learns the real secret
+        let real: Vec<u8> = fooled.iter().zip(&shift).map(|(a, b)| a ^ b).collect();
+        assert_eq!(real, secret);
+
+        // one spare share catches the forgery, two more pin it on the cheater
+        let mut handed_in = shares.clone();
+        handed_in[0] = forged;
+        assert!(combine_checked(&handed_in[..4], 3).is_err());
+        assert_eq!(
+            combine_checked(&handed_in[..5], 3),
+            Err(ShareError::Inconsistent(vec![1]))
+        );
+        assert_eq!(combine_checked(&shares, 3).unwrap(), secret);
+    }
+}
@@ -0,0 +1,25 @@
+use crate::bytes::xor_byte_array;
+
+// Stream cyphers addressed by byte offset, so any part of a message can be
+// encrypted, decrypted or rewritten without touching the rest.
+pub trait StreamCypher {
+    fn keystream(&self, offset: usize, length: usize) -> Vec<u8>;
+
+    // encryption and decryption are the same
+    fn apply(&self, offset: usize, data: &[u8]) -> Vec<u8> {
+        if data.is_empty() {
+            return vec![];
+        }
+        xor_byte_array(data, &self.keystream(offset, data.len()))
+    }
+
+    // re-encrypt newtext over cyphertext from offset on, as a disk would
+    // rewrite a sector
+    fn edit(&self, cyphertext: &mut [u8], offset: usize, newtext: &[u8]) {
+        assert!(
+            offset + newtext.len() <= cyphertext.len(),
+            "edit past the end"
+        );
+        cyphertext[offset..offset + newtext.len()].copy_from_slice(&self.apply(offset, newtext));
+    }
+}
@@ -0,0 +1,137 @@
+use crate::gf2::{BitVec, Echelon};
+
+// xorshift128+ as V8 runs it behind Math.random. The double is built from
+// the top 52 bits of state0 alone, which makes every output bit linear in
+// the initial state.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct XorShift128 {
+    pub state0: u64,
+    pub state1: u64,
+}
+
+pub fn to_double(state0: u64) -> f64 {
+    f64::from_bits(state0 >> 12 | 0x3ff0000000000000) - 1.0
+}
+
+// the 52 bits a double keeps, back in place
+pub fn from_double(x: f64) -> u64 {
+    ((x + 1.0).to_bits() & 0x000fffffffffffff) << 12
+}
+
+impl XorShift128 {
+    pub fn new(state0: u64, state1: u64) -> Self {
+        XorShift128 { state0, state1 }
+    }
+
+    pub fn step(&mut self) {
+        let mut s1 = self.state0;
+        let s0 = self.state1;
+        self.state0 = s0;
+        s1 ^= s1 << 23;
+        s1 ^= s1 >> 17;
+        s1 ^= s0;
+        s1 ^= s0 >> 26;
+        self.state1 = s1;
+    }
+
+    // Math.random fills a cache of 64 of these and hands them out last
+    // first, so observed values need reversing within each batch
+    pub fn next_f64(&mut self) -> f64 {
+        self.step();
+        to_double(self.state0)
+    }
+}
+
+// every bit of a state word as a combination of the 128 unknown state bits
+type Symbolic = Vec<BitVec>;
+
+fn xor(x: &Symbolic, y: &Symbolic) -> Symbolic {
+    x.iter().zip(y).map(|(x, y)| x ^ y).collect()
+}
+
+fn shl(x: &Symbolic, n: usize) -> Symbolic {
+    (0..64)
+        .map(|i| {
+            if i >= n {
+                x[i - n].clone()
+            } else {
+                BitVec::zeros(128)
+            }
+        })
+        .collect()
+}
+
+fn shr(x: &Symbolic, n: usize) -> Symbolic {
+    (0..64)
+        .map(|i| {
+            if i + n < 64 {
+                x[i + n].clone()
+            } else {
+                BitVec::zeros(128)
+            }
+        })
+        .collect()
+}
+
+// Solve for the state from consecutive doubles in generation order; three
+// are usually enough. The result continues after the last one.
+pub fn recover(outputs: &[f64]) -> Option<XorShift128> {
+    let mut state0: Symbolic = (0..64).map(|i| BitVec::unit(128, i)).collect();
+    let mut state1: Symbolic = (0..64).map(|i| BitVec::unit(128, 64 + i)).collect();
+    let mut system = Echelon::new(128);
+    for output in outputs {
+        let mut s1 = state0;
+        let s0 = state1;
+        state0 = s0.clone();
+        s1 = xor(&s1, &shl(&s1, 23));
+        s1 = xor(&s1, &shr(&s1, 17));
+        s1 = xor(&s1, &s0);
+        s1 = xor(&s1, &shr(&s0, 26));
+        state1 = s1;
+
+        let known = from_double(*output);
+        for (bit, combination) in state0.iter().enumerate().skip(12) {
+            system
+                .insert(combination.clone(), known >> bit & 1 == 1)
+                .ok()?;
+        }
+    }
+    if system.rank() < 128 {
+        return None;
+    }
+    let solution = system.solve();
+    let mut recovered = XorShift128::new(solution.words()[0], solution.words()[1]);
+    for _ in outputs {
+        recovered.step();
+    }
+    Some(recovered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_double() {
+        assert_eq!(to_double(0), 0.0);
+        assert_eq!(to_double(1 << 63), 0.5);
+        assert!(to_double(!0) < 1.0);
+        assert_eq!(
+            from_double(to_double(0xdeadbeefcafef00d)),
+            0xdeadbeefcafef000
+        );
+    }
+
+    #[test]
+    fn test_recover() {
+        let mut generator = XorShift128::new(0x0123456789abcdef, 0xfedcba9876543210);
+        let outputs: Vec<f64> = (0..4).map(|_| generator.next_f64()).collect();
+        let mut recovered = recover(&outputs).unwrap();
+        assert_eq!(recovered, generator);
+        for _ in 0..100 {
+            assert_eq!(recovered.next_f64(), generator.next_f64());
+        }
+        assert!(recover(&outputs[..1]).is_none());
+    }
+}